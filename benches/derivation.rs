@@ -0,0 +1,62 @@
+//! Compares deriving every address from the root, like `Wallet::new_address` used to, with
+//! `Wallet::new_addresses`, which derives them from the chain key cached for the session.
+//!
+//! Run with `cargo bench --bench derivation`.
+
+use std::{env, fs, time::Instant};
+
+use bip32::{PublicKey as _, Seed};
+use cryptowallet::wallet::core::{derive_child, CoinType, NewAddressParams, Wallet};
+use cryptowallet::wallet::path::Purpose;
+use cryptowallet::wallet::wallet_bitcoin::p2wpkh_address_from_compressed_pub_key;
+
+const PHRASE: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const ADDRESSES: u32 = 1000;
+
+fn main() {
+    let mnemonic = bip39::Mnemonic::parse(PHRASE).unwrap();
+
+    // seed and full path for every address
+    let start = Instant::now();
+    let mut from_root_addresses = Vec::new();
+    for index in 0..ADDRESSES {
+        let seed = Seed::new(mnemonic.to_seed(""));
+        let path = format!("m/84'/0'/0'/0/{}", index).parse().unwrap();
+        let key = derive_child(&seed, &path).unwrap();
+        let address = p2wpkh_address_from_compressed_pub_key(
+            key.pub_key.public_key().to_bytes(),
+            bitcoin::Network::Bitcoin,
+        )
+        .unwrap();
+        from_root_addresses.push(address.to_string());
+    }
+    let from_root = start.elapsed();
+
+    // the batch includes the single save of the wallet file
+    let path = env::temp_dir().join(format!("wolet_bench_{}.json", std::process::id()));
+    let mut wallet = Wallet::from_mnemonic_at(&path, PHRASE, "bench", None).unwrap();
+    wallet
+        .new_account(CoinType::Bitcoin, Purpose::Bip84, "bench")
+        .unwrap();
+    let prms = NewAddressParams::new(CoinType::Bitcoin, 0).purpose(Purpose::Bip84);
+    let start = Instant::now();
+    let addresses = wallet.new_addresses(prms, ADDRESSES).unwrap();
+    let cached = start.elapsed();
+    fs::remove_file(&path).unwrap();
+
+    let cached_addresses: Vec<String> = addresses
+        .into_iter()
+        .map(|address| address.address)
+        .collect();
+    assert_eq!(cached_addresses, from_root_addresses);
+
+    println!(
+        "{} addresses derived from the root:   {:?}",
+        ADDRESSES, from_root
+    );
+    println!(
+        "{} addresses from cached chain key:   {:?}",
+        ADDRESSES, cached
+    );
+}
@@ -1,18 +1,24 @@
-mod ui;
-mod wallet;
-
-use std::env;
-
 use anyhow::Result;
+use cryptowallet::{ui, wallet};
 use std::time::Duration;
+use tuirealm::props::{AttrValue, Attribute, PropPayload, PropValue, TextSpan};
 use tuirealm::terminal::TerminalBridge;
 use tuirealm::{application::PollStrategy, Application, EventListenerCfg, NoUserEvent, Update};
+use ui::account_overview::AccountOverview;
+use ui::confirm_prompt::ConfirmPrompt;
 use ui::data::Msg;
+use ui::key_export::KeyExport;
 use ui::main_menu::MainMenu;
+use ui::passphrase_input::PassphraseInput;
+use ui::password_input::PasswordInput;
+use ui::phrase_input::PhraseInput;
+use ui::status_bar::StatusBar;
 use ui::wallet_actions::WalletActions;
 use wallet::{
-    core::Wallet,
-    evm::{address_from_pubkey, establish_web3_connection},
+    coins::Curve,
+    core::{parse_mnemonic, Bip44ChangeVal, Wallet, DEFAULT_WORD_COUNT},
+    evm_chains::{ChainRegistry, SEPOLIA},
+    labels::Annotations,
 };
 // tui
 use tuirealm::tui::layout::{Constraint, Direction as LayoutDirection, Layout};
@@ -22,40 +28,166 @@ use tuirealm::tui::layout::{Constraint, Direction as LayoutDirection, Layout};
 pub enum Id {
     MainMenu,
     WalletActions,
+    PasswordInput,
+    PassphraseInput,
+    PhraseInput,
+    ConfirmPrompt,
+    KeyExport,
+    AccountOverview,
+    Status,
 }
 
-struct WoletState {
-    wallet: Option<Wallet>,
+/// What to do with the wallet file once the password has been entered
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WalletSource {
+    Load,
+    Create,
+    Restore(String),
 }
 
-impl Default for WoletState {
-    fn default() -> Self {
-        Self { wallet: None }
-    }
+#[derive(Default)]
+struct WoletState {
+    wallet: Option<Wallet>,
+    pending: Option<WalletSource>,
+    // true while the recovery phrase prompt is shown, before asking for a password
+    entering_phrase: bool,
+    // set once the password is entered, while waiting for the optional BIP39 passphrase
+    password: Option<String>,
+    // true while asking whether to show the extended private keys
+    confirming_private_export: bool,
+    showing_keys: bool,
+    showing_accounts: bool,
 }
 
 impl WoletState {
-    fn new_wallet(&mut self) {
-        // TODO remove unwrap
-        let new_wallet = Wallet::new().unwrap();
+    fn new_wallet(&mut self, password: &str, passphrase: Option<&str>) -> Result<()> {
+        let new_wallet = Wallet::new(DEFAULT_WORD_COUNT, password, passphrase)?;
         self.wallet = Some(new_wallet);
+        Ok(())
     }
 
-    fn load_wallet_from_file(&mut self) {
-        // TODO remove unwrap or default
-        let loaded_wallet = Wallet::from_file().unwrap();
+    fn load_wallet_from_file(&mut self, password: &str, passphrase: Option<&str>) -> Result<()> {
+        let loaded_wallet = Wallet::from_file(password, passphrase)?;
         self.wallet = Some(loaded_wallet);
+        Ok(())
+    }
+
+    fn restore_wallet(
+        &mut self,
+        phrase: &str,
+        password: &str,
+        passphrase: Option<&str>,
+    ) -> Result<()> {
+        let restored_wallet = Wallet::from_mnemonic(phrase, password, passphrase)?;
+        self.wallet = Some(restored_wallet);
+        Ok(())
+    }
+
+    /// One line per account of the open wallet with its exported extended key
+    fn export_keys(&self, private: bool) -> Result<Vec<String>> {
+        let wallet = match &self.wallet {
+            Some(wallet) => wallet,
+            None => return Ok(Vec::new()),
+        };
+        let mut accounts = Vec::new();
+        for (coin, purposes) in &wallet.coins {
+            // ed25519 accounts have no extended keys
+            if coin.adapter().curve() != Curve::Secp256k1 {
+                continue;
+            }
+            for (purpose, coin_accts) in purposes {
+                for account in coin_accts.accounts.values() {
+                    accounts.push((*coin, *purpose, account.index, account.name.clone()));
+                }
+            }
+        }
+        accounts.sort_by_key(|(coin, purpose, index, _)| (*coin, *purpose as u32, *index));
+
+        let mut lines = Vec::new();
+        for (coin, purpose, index, name) in accounts {
+            let export = match private {
+                true => wallet.export_account_xprv(coin, purpose, index)?,
+                false => wallet.export_account_xpub(coin, purpose, index)?,
+            };
+            lines.push(format!("{} ({:?}): {}", name, coin, export));
+        }
+        Ok(lines)
+    }
+
+    /// Every account of the open wallet followed by its addresses, with labels and annotations
+    fn account_overview(&self) -> Vec<String> {
+        let wallet = match &self.wallet {
+            Some(wallet) => wallet,
+            None => return Vec::new(),
+        };
+        let mut accounts = Vec::new();
+        for (coin, purposes) in &wallet.coins {
+            for (purpose, coin_accts) in purposes {
+                for account in coin_accts.accounts.values() {
+                    accounts.push((*coin, *purpose, account));
+                }
+            }
+        }
+        accounts.sort_by_key(|(coin, purpose, account)| (*coin, *purpose as u32, account.index));
+
+        let mut lines = Vec::new();
+        for (coin, purpose, account) in accounts {
+            lines.push(format!(
+                "{} ({:?} m/{}'/{}'/{}'){}",
+                account.name,
+                coin,
+                purpose,
+                coin,
+                account.index,
+                annotation_suffix(&account.annotations)
+            ));
+            for change in [Bip44ChangeVal::RECEIVING, Bip44ChangeVal::INTERNAL] {
+                let mut addresses: Vec<_> = account
+                    .changes
+                    .get(&change)
+                    .map(|chain| chain.addresses.iter().collect())
+                    .unwrap_or_default();
+                addresses.sort_by_key(|(index, _)| **index);
+                for (_, address) in addresses {
+                    lines.push(format!(
+                        "  {} {} {}{}",
+                        address.path,
+                        address.address_checksummed,
+                        address.label.as_deref().unwrap_or(""),
+                        annotation_suffix(&address.annotations)
+                    ));
+                }
+            }
+        }
+        lines
     }
 
+    #[allow(dead_code)]
     async fn test_connection(&mut self) {
         // TODO remove all unwraps
-        let endpoint = env::var("TESTNET_WS").unwrap();
-        let web3_con = establish_web3_connection(&endpoint).await.unwrap();
+        let registry = ChainRegistry::from_env();
+        let chain = registry.get(SEPOLIA).unwrap();
+        let web3_con = chain.connect().await.unwrap();
         let block_number = web3_con.eth().block_number().await.unwrap();
-        println!("block number: {}", &block_number);
+        println!("{} block number: {}", chain.name, &block_number);
     }
 }
 
+/// ` [tag, tag] (archived) - note`, empty without annotations
+fn annotation_suffix(annotations: &Annotations) -> String {
+    let mut suffix = String::new();
+    if !annotations.tags.is_empty() {
+        suffix.push_str(&format!(" [{}]", annotations.tags.join(", ")));
+    }
+    if annotations.archived {
+        suffix.push_str(" (archived)");
+    }
+    if let Some(note) = &annotations.note {
+        suffix.push_str(&format!(" - {}", note));
+    }
+    suffix
+}
+
 struct Wolet {
     // ui fields
     quit: bool,   // Becomes true when the user presses <ESC>
@@ -81,13 +213,49 @@ impl Default for Wolet {
                 vec![]
             )
             .is_ok());
+        assert!(app
+            .mount(
+                Id::PasswordInput,
+                Box::new(PasswordInput::default()),
+                vec![]
+            )
+            .is_ok());
+        assert!(app
+            .mount(
+                Id::PassphraseInput,
+                Box::new(PassphraseInput::default()),
+                vec![]
+            )
+            .is_ok());
+        assert!(app
+            .mount(Id::PhraseInput, Box::new(PhraseInput::default()), vec![])
+            .is_ok());
+        assert!(app
+            .mount(
+                Id::ConfirmPrompt,
+                Box::new(ConfirmPrompt::default()),
+                vec![]
+            )
+            .is_ok());
+        assert!(app
+            .mount(Id::KeyExport, Box::new(KeyExport::default()), vec![])
+            .is_ok());
+        assert!(app
+            .mount(
+                Id::AccountOverview,
+                Box::new(AccountOverview::default()),
+                vec![]
+            )
+            .is_ok());
+        assert!(app
+            .mount(Id::Status, Box::new(StatusBar::default()), vec![])
+            .is_ok());
         // We need to give focus to input then
         assert!(app.active(&Id::MainMenu).is_ok());
-        assert!(app.active(&Id::WalletActions).is_ok());
         Self {
             quit: false,
             redraw: true,
-            states: WoletState { wallet: None },
+            states: WoletState::default(),
             app,
         }
     }
@@ -103,21 +271,140 @@ impl Wolet {
                 .constraints(
                     [
                         Constraint::Length(10),
-                        Constraint::Length(6),
+                        Constraint::Min(6),
                         Constraint::Length(1),
                     ]
                     .as_ref(),
                 )
                 .split(f.size());
             if self.states.wallet.is_some() {
-                // show main menu
+                // show wallet actions menu
                 self.app.view(&Id::WalletActions, f, chunks[0]);
+                if self.states.confirming_private_export {
+                    self.app.view(&Id::ConfirmPrompt, f, chunks[1]);
+                } else if self.states.showing_keys {
+                    self.app.view(&Id::KeyExport, f, chunks[1]);
+                } else if self.states.showing_accounts {
+                    self.app.view(&Id::AccountOverview, f, chunks[1]);
+                }
             } else {
-                // show wallet actions menu
+                // show main menu
                 self.app.view(&Id::MainMenu, f, chunks[0]);
+                if self.states.entering_phrase {
+                    self.app.view(&Id::PhraseInput, f, chunks[1]);
+                } else if self.states.password.is_some() {
+                    self.app.view(&Id::PassphraseInput, f, chunks[1]);
+                } else if self.states.pending.is_some() {
+                    self.app.view(&Id::PasswordInput, f, chunks[1]);
+                }
             }
+            self.app.view(&Id::Status, f, chunks[2]);
         });
     }
+
+    fn set_status(&mut self, status: &str) {
+        let _ = self.app.attr(
+            &Id::Status,
+            Attribute::Text,
+            AttrValue::String(status.to_string()),
+        );
+    }
+
+    /// Unlocks or creates the wallet once both the password and passphrase prompts are submitted
+    fn open_wallet(&mut self, passphrase: &str) {
+        let password = self.states.password.take().unwrap_or_default();
+        // an empty passphrase is the same as not using one
+        let passphrase = Some(passphrase).filter(|passphrase| !passphrase.is_empty());
+        let result = match self.states.pending.take() {
+            Some(WalletSource::Create) => self.states.new_wallet(&password, passphrase),
+            Some(WalletSource::Load) => self.states.load_wallet_from_file(&password, passphrase),
+            Some(WalletSource::Restore(phrase)) => {
+                self.states.restore_wallet(&phrase, &password, passphrase)
+            }
+            None => return,
+        };
+        // never keep the secrets around in the input components
+        self.clear_secret_inputs();
+        match result {
+            Ok(()) => {
+                self.set_status("");
+                let _ = self.app.active(&Id::WalletActions);
+            }
+            Err(err) => {
+                self.set_status(&err.to_string());
+                let _ = self.app.active(&Id::MainMenu);
+            }
+        }
+    }
+
+    fn clear_secret_inputs(&mut self) {
+        for id in [Id::PhraseInput, Id::PasswordInput, Id::PassphraseInput] {
+            let _ = self
+                .app
+                .attr(&id, Attribute::Value, AttrValue::String(String::new()));
+        }
+    }
+
+    /// Shows the extended keys of every account in the open wallet
+    fn show_keys(&mut self, private: bool) {
+        let lines = match self.states.export_keys(private) {
+            Ok(lines) => lines,
+            Err(err) => {
+                self.set_status(&err.to_string());
+                return;
+            }
+        };
+        if lines.is_empty() {
+            self.set_status("wallet has no accounts yet");
+            return;
+        }
+        let rows = lines
+            .into_iter()
+            .map(|line| PropValue::TextSpan(TextSpan::from(line)))
+            .collect();
+        let _ = self.app.attr(
+            &Id::KeyExport,
+            Attribute::Text,
+            AttrValue::Payload(PropPayload::Vec(rows)),
+        );
+        self.set_status("");
+        self.states.showing_keys = true;
+        let _ = self.app.active(&Id::KeyExport);
+    }
+
+    /// Shows the accounts and addresses of the open wallet with their labels
+    fn show_accounts(&mut self) {
+        let lines = self.states.account_overview();
+        if lines.is_empty() {
+            self.set_status("wallet has no accounts yet");
+            return;
+        }
+        let rows = lines
+            .into_iter()
+            .map(|line| PropValue::TextSpan(TextSpan::from(line)))
+            .collect();
+        let _ = self.app.attr(
+            &Id::AccountOverview,
+            Attribute::Text,
+            AttrValue::Payload(PropPayload::Vec(rows)),
+        );
+        self.set_status("");
+        self.states.showing_accounts = true;
+        let _ = self.app.active(&Id::AccountOverview);
+    }
+
+    /// Checks the recovery phrase before moving on to the password prompt
+    fn submit_phrase(&mut self, phrase: String) {
+        if let Err(err) = parse_mnemonic(&phrase) {
+            self.set_status(&err.to_string());
+            return;
+        }
+        self.clear_secret_inputs();
+        self.set_status("");
+        self.states.entering_phrase = false;
+        self.states.pending = Some(WalletSource::Restore(phrase));
+        let _ = self.app.active(&Id::PasswordInput);
+    }
 }
 
 impl Update<Msg> for Wolet {
@@ -131,13 +418,85 @@ impl Update<Msg> for Wolet {
             Msg::MainMenuBlur => None,
             Msg::WalletActionsBlur => None,
             Msg::OptionSelected(val) => {
-                if val == 1 {
-                    self.states.new_wallet();
-                    None
+                if self.states.wallet.is_none() {
+                    match val {
+                        1 => self.states.pending = Some(WalletSource::Create),
+                        2 => self.states.entering_phrase = true,
+                        _ => self.states.pending = Some(WalletSource::Load),
+                    }
+                    if self.states.entering_phrase {
+                        let _ = self.app.active(&Id::PhraseInput);
+                    } else {
+                        let _ = self.app.active(&Id::PasswordInput);
+                    }
                 } else {
-                    self.states.load_wallet_from_file();
-                    None
+                    match val {
+                        0 => self.show_keys(false),
+                        1 => {
+                            self.states.confirming_private_export = true;
+                            let _ = self.app.active(&Id::ConfirmPrompt);
+                        }
+                        _ => self.show_accounts(),
+                    }
                 }
+                None
+            }
+            Msg::PrivateExportConfirmed(confirmed) => {
+                self.states.confirming_private_export = false;
+                // always default back to "No"
+                let _ = self.app.attr(
+                    &Id::ConfirmPrompt,
+                    Attribute::Value,
+                    AttrValue::Payload(PropPayload::One(PropValue::Usize(0))),
+                );
+                if confirmed {
+                    self.show_keys(true);
+                } else {
+                    let _ = self.app.active(&Id::WalletActions);
+                }
+                None
+            }
+            Msg::KeyExportClosed => {
+                // never leave private keys in the component once closed
+                let _ = self.app.attr(
+                    &Id::KeyExport,
+                    Attribute::Text,
+                    AttrValue::Payload(PropPayload::Vec(Vec::new())),
+                );
+                self.states.showing_keys = false;
+                let _ = self.app.active(&Id::WalletActions);
+                None
+            }
+            Msg::AccountOverviewClosed => {
+                self.states.showing_accounts = false;
+                let _ = self.app.active(&Id::WalletActions);
+                None
+            }
+            Msg::PasswordSubmitted(password) => {
+                self.states.password = Some(password);
+                let _ = self.app.active(&Id::PassphraseInput);
+                None
+            }
+            Msg::PassphraseSubmitted(passphrase) => {
+                self.open_wallet(&passphrase);
+                None
+            }
+            Msg::PasswordCancelled | Msg::PassphraseCancelled => {
+                self.states.pending = None;
+                self.states.password = None;
+                self.clear_secret_inputs();
+                let _ = self.app.active(&Id::MainMenu);
+                None
+            }
+            Msg::PhraseSubmitted(phrase) => {
+                self.submit_phrase(phrase);
+                None
+            }
+            Msg::PhraseCancelled => {
+                self.states.entering_phrase = false;
+                self.clear_secret_inputs();
+                let _ = self.app.active(&Id::MainMenu);
+                None
             }
 
             Msg::None => None,
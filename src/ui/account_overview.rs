@@ -0,0 +1,65 @@
+use super::data::Msg;
+use tui_realm_stdlib::Textarea;
+use tuirealm::command::{Cmd, Direction, Position};
+use tuirealm::props::{Alignment, BorderType, Borders, Color};
+use tuirealm::{
+    event::{Key, KeyEvent},
+    Component, Event, MockComponent, NoUserEvent,
+};
+
+/// Lists the accounts and addresses of the wallet with their labels, notes and tags
+#[derive(MockComponent)]
+pub struct AccountOverview {
+    component: Textarea,
+}
+
+impl Default for AccountOverview {
+    fn default() -> Self {
+        Self {
+            component: Textarea::default()
+                .borders(
+                    Borders::default()
+                        .modifiers(BorderType::Rounded)
+                        .color(Color::Yellow),
+                )
+                .foreground(Color::LightYellow)
+                .title(
+                    "📒 accounts and addresses (esc to close) 📒",
+                    Alignment::Center,
+                )
+                .step(4),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for AccountOverview {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _ = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. })
+            | Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => return Some(Msg::AccountOverviewClosed),
+            _ => return None,
+        };
+        Some(Msg::None)
+    }
+}
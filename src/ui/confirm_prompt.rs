@@ -0,0 +1,63 @@
+use super::data::Msg;
+use tui_realm_stdlib::Radio;
+use tuirealm::command::{Cmd, CmdResult, Direction};
+use tuirealm::props::{Alignment, BorderType, Borders, Color};
+use tuirealm::{
+    event::{Key, KeyEvent},
+    Component, Event, MockComponent, NoUserEvent, State, StateValue,
+};
+
+/// Yes/no question asked before showing extended private keys
+#[derive(MockComponent)]
+pub struct ConfirmPrompt {
+    component: Radio,
+}
+
+impl Default for ConfirmPrompt {
+    fn default() -> Self {
+        Self {
+            component: Radio::default()
+                .borders(
+                    Borders::default()
+                        .modifiers(BorderType::Rounded)
+                        .color(Color::LightRed),
+                )
+                .foreground(Color::LightRed)
+                .title(
+                    "⚠️ show private keys? anyone who sees them can spend your funds ⚠️",
+                    Alignment::Center,
+                )
+                .rewind(true)
+                .choices(&["No", "Yes"])
+                .value(0),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfirmPrompt {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _ = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::PrivateExportConfirmed(false))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                if let CmdResult::Submit(State::One(StateValue::Usize(choice))) =
+                    self.perform(Cmd::Submit)
+                {
+                    return Some(Msg::PrivateExportConfirmed(choice == 1));
+                }
+                CmdResult::None
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
@@ -4,5 +4,14 @@ pub enum Msg {
     MainMenuBlur,
     WalletActionsBlur,
     OptionSelected(usize),
+    PasswordSubmitted(String),
+    PasswordCancelled,
+    PassphraseSubmitted(String),
+    PassphraseCancelled,
+    PhraseSubmitted(String),
+    PhraseCancelled,
+    PrivateExportConfirmed(bool),
+    KeyExportClosed,
+    AccountOverviewClosed,
     None,
 }
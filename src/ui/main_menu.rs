@@ -36,6 +36,12 @@ impl Default for MainMenu {
                         .add_col(TextSpan::from("02").fg(Color::Cyan).italic())
                         .add_col(TextSpan::from(" "))
                         .add_col(TextSpan::from("Create New (will overwrite old wallet)"))
+                        .add_row()
+                        .add_col(TextSpan::from("03").fg(Color::Cyan).italic())
+                        .add_col(TextSpan::from(" "))
+                        .add_col(TextSpan::from(
+                            "Restore from phrase (will overwrite old wallet)",
+                        ))
                         .build(),
                 )
                 .selected_line(2),
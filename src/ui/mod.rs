@@ -1,3 +1,10 @@
+pub mod account_overview;
+pub mod confirm_prompt;
 pub mod data;
+pub mod key_export;
 pub mod main_menu;
+pub mod passphrase_input;
+pub mod password_input;
+pub mod phrase_input;
+pub mod status_bar;
 pub mod wallet_actions;
@@ -0,0 +1,77 @@
+use super::data::Msg;
+use tui_realm_stdlib::Input;
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::props::{Alignment, BorderType, Borders, Color, InputType};
+use tuirealm::{
+    event::{Key, KeyEvent},
+    Component, Event, MockComponent, NoUserEvent, State, StateValue,
+};
+
+#[derive(MockComponent)]
+pub struct PassphraseInput {
+    component: Input,
+}
+
+impl Default for PassphraseInput {
+    fn default() -> Self {
+        Self {
+            component: Input::default()
+                .borders(
+                    Borders::default()
+                        .modifiers(BorderType::Rounded)
+                        .color(Color::Yellow),
+                )
+                .foreground(Color::LightYellow)
+                .input_type(InputType::Password('*'))
+                .title(
+                    "🔑 BIP39 passphrase (leave empty for none) 🔑",
+                    Alignment::Center,
+                ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for PassphraseInput {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _ = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                ..
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::PassphraseCancelled)
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                if let CmdResult::Submit(State::One(StateValue::String(passphrase))) =
+                    self.perform(Cmd::Submit)
+                {
+                    return Some(Msg::PassphraseSubmitted(passphrase));
+                }
+                CmdResult::None
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
@@ -0,0 +1,24 @@
+use super::data::Msg;
+use tui_realm_stdlib::Label;
+use tuirealm::props::Color;
+use tuirealm::{Component, Event, MockComponent, NoUserEvent};
+
+/// Single line used to report errors such as a wrong wallet password
+#[derive(MockComponent)]
+pub struct StatusBar {
+    component: Label,
+}
+
+impl Default for StatusBar {
+    fn default() -> Self {
+        Self {
+            component: Label::default().foreground(Color::LightRed),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for StatusBar {
+    fn on(&mut self, _: Event<NoUserEvent>) -> Option<Msg> {
+        None
+    }
+}
@@ -31,11 +31,17 @@ impl Default for WalletActions {
                     TableBuilder::default()
                         .add_col(TextSpan::from("01").fg(Color::Cyan).italic())
                         .add_col(TextSpan::from(" "))
-                        .add_col(TextSpan::from("Testi1"))
+                        .add_col(TextSpan::from("Export account public keys"))
                         .add_row()
                         .add_col(TextSpan::from("02").fg(Color::Cyan).italic())
                         .add_col(TextSpan::from(" "))
-                        .add_col(TextSpan::from("Testi2)"))
+                        .add_col(TextSpan::from(
+                            "Export account private keys (asks for confirmation)",
+                        ))
+                        .add_row()
+                        .add_col(TextSpan::from("03").fg(Color::Cyan).italic())
+                        .add_col(TextSpan::from(" "))
+                        .add_col(TextSpan::from("Show accounts, addresses and labels"))
                         .build(),
                 )
                 .selected_line(2),
@@ -0,0 +1,199 @@
+use super::coins::{CoinAdapter, Curve, EncodedAddress};
+use super::core::{CoinType, PubKeyForm};
+use super::path::Purpose;
+use super::wallet_bitcoin::{address_from_compressed_pub_key, BitcoinAdapter};
+use anyhow::{Error, Result};
+use bitcoin::bech32::{u5, FromBase32, ToBase32};
+use bitcoin::hashes::Hash;
+use bitcoin::{address::Payload, Network, PubkeyHash, ScriptHash};
+
+// ERR MESSAGES
+const INVALID_COMPRESSED_KEY: &str = "expected a 33 byte compressed public key";
+const UNCOMPRESSED_KEY: &str = "bitcoin cash addresses hash the compressed key";
+const WRONG_PREFIX: &str = "cashaddr belongs to a different network";
+const MIXED_CASE: &str = "cashaddr must not mix upper and lower case";
+const INVALID_CHECKSUM: &str = "cashaddr checksum does not match";
+const INVALID_PAYLOAD: &str = "cashaddr must hold a 20 byte P2PKH or P2SH hash";
+
+/// Network prefix of mainnet CashAddr addresses
+pub const CASHADDR_PREFIX: &str = "bitcoincash";
+
+const CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const CHECKSUM_LEN: usize = 8;
+
+// version byte of the payload, the type in bits 3-6 and a size of 0 for 160 bit hashes
+const P2PKH_VERSION: u8 = 0;
+const P2SH_VERSION: u8 = 1 << 3;
+
+/// `CoinAdapter` of Bitcoin Cash. Keys and hashes are the same as bitcoin P2PKH, addresses are
+/// stored in CashAddr (`bitcoincash:q...`) and the legacy `1...` form is accepted everywhere.
+pub struct BitcoinCashAdapter {
+    legacy: BitcoinAdapter,
+}
+
+impl BitcoinCashAdapter {
+    /// `legacy` encodes and decodes the base58 form
+    pub const fn new(legacy: BitcoinAdapter) -> Self {
+        BitcoinCashAdapter { legacy }
+    }
+
+    /// The legacy base58 form of a CashAddr or legacy address
+    pub fn legacy_address(&self, address: &str) -> Result<String> {
+        Ok(self.legacy.encode_payload(&self.decode_payload(address)?))
+    }
+
+    fn decode_payload(&self, address: &str) -> Result<Payload> {
+        let address = address.trim();
+        match self.legacy.decode_payload(address) {
+            Ok(payload) => Ok(payload),
+            Err(_) => decode_cashaddr(address),
+        }
+    }
+}
+
+impl CoinAdapter for BitcoinCashAdapter {
+    fn coin(&self) -> CoinType {
+        self.legacy.coin()
+    }
+
+    fn slip44(&self) -> u32 {
+        self.legacy.slip44()
+    }
+
+    fn curve(&self) -> Curve {
+        Curve::Secp256k1
+    }
+
+    fn purposes(&self) -> &'static [Purpose] {
+        self.legacy.purposes()
+    }
+
+    fn encode_address(
+        &self,
+        pub_key: &[u8],
+        _purpose: Option<Purpose>,
+        key_form: PubKeyForm,
+    ) -> Result<EncodedAddress> {
+        if key_form == PubKeyForm::Uncompressed {
+            return Err(Error::msg(UNCOMPRESSED_KEY));
+        }
+        let pub_key: [u8; 33] = pub_key
+            .try_into()
+            .map_err(|_| Error::msg(INVALID_COMPRESSED_KEY))?;
+        let payload = address_from_compressed_pub_key(pub_key, Network::Bitcoin)?.payload;
+        let address = encode_cashaddr(&payload)?;
+        Ok(EncodedAddress {
+            checksummed: address.clone(),
+            address,
+            key_form,
+        })
+    }
+
+    /// Accepts CashAddr with or without its prefix and the legacy form, returns the CashAddr.
+    fn checksum_address(&self, address: &str) -> Result<String> {
+        encode_cashaddr(&self.decode_payload(address)?)
+    }
+
+    /// Upper case CashAddr is as valid as lower case, legacy base58 addresses are case sensitive
+    fn case_insensitive(&self, address: &str) -> bool {
+        decode_cashaddr(address).is_ok()
+    }
+
+    fn address_aliases(&self, address: &str) -> Vec<String> {
+        let mut aliases = Vec::new();
+        if let Ok(legacy) = self.legacy_address(address) {
+            aliases.push(legacy);
+        }
+        if let Some((_, unprefixed)) = address.split_once(':') {
+            aliases.push(unprefixed.to_string());
+        }
+        aliases
+    }
+}
+
+/// `bitcoincash:` followed by the version byte and hash of `payload`, with a BCH code checksum.
+pub fn encode_cashaddr(payload: &Payload) -> Result<String> {
+    let (version, hash) = match payload {
+        Payload::PubkeyHash(hash) => (P2PKH_VERSION, hash.to_byte_array()),
+        Payload::ScriptHash(hash) => (P2SH_VERSION, hash.to_byte_array()),
+        _ => return Err(Error::msg(INVALID_PAYLOAD)),
+    };
+    let mut data = [&[version], &hash[..]].concat().to_base32();
+    let checksum = polymod(&checksum_input(CASHADDR_PREFIX, &data, true));
+    for group in 0..CHECKSUM_LEN {
+        let value = (checksum >> (5 * (CHECKSUM_LEN - 1 - group))) & 0x1f;
+        data.push(u5::try_from_u8(value as u8)?);
+    }
+    let encoded: String = data.iter().map(|value| value.to_char()).collect();
+    Ok(format!("{}:{}", CASHADDR_PREFIX, encoded))
+}
+
+/// Parses a CashAddr, the `bitcoincash:` prefix is optional.
+pub fn decode_cashaddr(address: &str) -> Result<Payload> {
+    let address = address.trim();
+    if address.chars().any(|c| c.is_ascii_lowercase())
+        && address.chars().any(|c| c.is_ascii_uppercase())
+    {
+        return Err(Error::msg(MIXED_CASE));
+    }
+    let address = address.to_lowercase();
+    let encoded = match address.split_once(':') {
+        Some((prefix, encoded)) if prefix == CASHADDR_PREFIX => encoded,
+        Some(_) => return Err(Error::msg(WRONG_PREFIX)),
+        None => &address,
+    };
+    let data = encoded
+        .chars()
+        .map(|c| {
+            let value = CHARSET.find(c).ok_or(Error::msg(INVALID_CHECKSUM))?;
+            Ok(u5::try_from_u8(value as u8)?)
+        })
+        .collect::<Result<Vec<u5>>>()?;
+    if data.len() <= CHECKSUM_LEN || polymod(&checksum_input(CASHADDR_PREFIX, &data, false)) != 0 {
+        return Err(Error::msg(INVALID_CHECKSUM));
+    }
+    let bytes = Vec::<u8>::from_base32(&data[..data.len() - CHECKSUM_LEN])?;
+    match bytes.split_first() {
+        Some((&P2PKH_VERSION, hash)) if hash.len() == 20 => {
+            Ok(Payload::PubkeyHash(PubkeyHash::from_slice(hash)?))
+        }
+        Some((&P2SH_VERSION, hash)) if hash.len() == 20 => {
+            Ok(Payload::ScriptHash(ScriptHash::from_slice(hash)?))
+        }
+        _ => Err(Error::msg(INVALID_PAYLOAD)),
+    }
+}
+
+// lower 5 bits of the prefix, a zero separator, the data and, when computing it, a zeroed
+// checksum
+fn checksum_input(prefix: &str, data: &[u5], with_template: bool) -> Vec<u8> {
+    let mut input: Vec<u8> = prefix.bytes().map(|byte| byte & 0x1f).collect();
+    input.push(0);
+    input.extend(data.iter().map(|value| value.to_u8()));
+    if with_template {
+        input.extend([0; CHECKSUM_LEN]);
+    }
+    input
+}
+
+// the 40 bit BCH code of the CashAddr spec
+fn polymod(values: &[u8]) -> u64 {
+    const GENERATORS: [u64; 5] = [
+        0x98f2bc8e61,
+        0x79b76d99e2,
+        0xf33e5fb3c4,
+        0xae2eabe2a8,
+        0x1e4f43e470,
+    ];
+    let mut checksum: u64 = 1;
+    for value in values {
+        let top = checksum >> 35;
+        checksum = ((checksum & 0x07ffffffff) << 5) ^ u64::from(*value);
+        for (bit, generator) in GENERATORS.iter().enumerate() {
+            if (top >> bit) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum ^ 1
+}
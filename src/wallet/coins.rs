@@ -0,0 +1,189 @@
+use super::bitcoin_cash::BitcoinCashAdapter;
+use super::core::{Bip44ChangeVal, CoinType, PubKeyForm};
+use super::cosmos::CosmosAdapter;
+use super::evm::EthereumAdapter;
+use super::path::{DerivationPath, Purpose, MAX_CHILD_INDEX};
+use super::solana::SolanaAdapter;
+use super::tron::TronAdapter;
+use super::wallet_bitcoin::{self, BitcoinAdapter};
+use anyhow::Result;
+use bip32::Prefix;
+use bitcoin::Network;
+
+/// Curve the keys of a coin live on
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Curve {
+    Secp256k1,
+    /// SLIP-10 ed25519, hardened derivation only
+    Ed25519,
+}
+
+/// An address in the two forms the wallet stores.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedAddress {
+    pub address: String,
+    /// Same as `address` for chains whose encoding always carries a checksum
+    pub checksummed: String,
+    /// Serialisation of the key that was actually hashed
+    pub key_form: PubKeyForm,
+}
+
+/// Everything chain specific the wallet needs, from the SLIP-44 index to address encoding.
+///
+/// Supporting a new chain means implementing this, adding a `CoinType` variant and registering
+/// the adapter in `adapter`. Nothing else in the wallet matches on `CoinType`.
+pub trait CoinAdapter: Send + Sync {
+    fn coin(&self) -> CoinType;
+
+    /// SLIP-44 coin type, the `coin'` level of every path
+    fn slip44(&self) -> u32;
+
+    fn curve(&self) -> Curve;
+
+    /// Purposes accounts can be created under, every coin supports `Purpose::Bip44`
+    fn purposes(&self) -> &'static [Purpose];
+
+    /// Path of address `index` on the `change` chain of `account`,
+    /// `m/purpose'/coin'/account'/change/index` unless the chain uses another layout
+    fn address_path(
+        &self,
+        purpose: Purpose,
+        account: u32,
+        change: Bip44ChangeVal,
+        index: u32,
+    ) -> Result<DerivationPath> {
+        DerivationPath::for_address(purpose, self.slip44(), account, change.index(), index)
+    }
+
+    /// How many addresses one chain of an account can hold
+    fn addresses_per_chain(&self) -> u32 {
+        MAX_CHILD_INDEX + 1
+    }
+
+    /// Address of `pub_key` under `purpose`, compressed for secp256k1 coins.
+    ///
+    /// `key_form` is what the caller asks for, chains that always hash one form ignore it and
+    /// report the form they used in the result.
+    fn encode_address(
+        &self,
+        pub_key: &[u8],
+        purpose: Option<Purpose>,
+        key_form: PubKeyForm,
+    ) -> Result<EncodedAddress>;
+
+    /// Checks that `address` is valid on this chain and returns its checksummed form.
+    fn checksum_address(&self, address: &str) -> Result<String>;
+
+    fn validate_address(&self, address: &str) -> bool {
+        self.checksum_address(address).is_ok()
+    }
+
+    /// Whether `address` matches regardless of case, like EVM hex addresses
+    fn case_insensitive(&self, _address: &str) -> bool {
+        false
+    }
+
+    /// Other forms `address` is known by, e.g. the legacy form of a CashAddr. The wallet finds
+    /// addresses by these too.
+    fn address_aliases(&self, _address: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The network of the `bitcoin` crate addresses of this coin can be checked against, `None`
+    /// for everything but bitcoin and its test networks
+    fn bitcoin_network(&self) -> Option<Network> {
+        None
+    }
+
+    /// SLIP-132 version of account extended public keys under `purpose`
+    fn public_prefix(&self, _purpose: Purpose) -> Prefix {
+        Prefix::XPUB
+    }
+
+    /// SLIP-132 version of account extended private keys under `purpose`
+    fn private_prefix(&self, _purpose: Purpose) -> Prefix {
+        Prefix::XPRV
+    }
+
+    /// Output descriptor wrapping keys under `purpose`, e.g. `sh(wpkh` for BIP49. `None` for
+    /// chains without descriptors.
+    fn descriptor(&self, _purpose: Purpose) -> Option<&'static str> {
+        None
+    }
+}
+
+static BITCOIN: BitcoinAdapter = BitcoinAdapter::new(CoinType::Bitcoin, 0, wallet_bitcoin::BITCOIN);
+static BITCOIN_TESTNET: BitcoinAdapter =
+    BitcoinAdapter::new(CoinType::BitcoinTestnet, 1, wallet_bitcoin::BITCOIN_TESTNET);
+static BITCOIN_SIGNET: BitcoinAdapter =
+    BitcoinAdapter::new(CoinType::BitcoinSignet, 1, wallet_bitcoin::BITCOIN_SIGNET);
+static BITCOIN_REGTEST: BitcoinAdapter =
+    BitcoinAdapter::new(CoinType::BitcoinRegtest, 1, wallet_bitcoin::BITCOIN_REGTEST);
+static LITECOIN: BitcoinAdapter =
+    BitcoinAdapter::new(CoinType::Litecoin, 2, wallet_bitcoin::LITECOIN);
+static DOGECOIN: BitcoinAdapter =
+    BitcoinAdapter::new(CoinType::Dogecoin, 3, wallet_bitcoin::DOGECOIN);
+static BITCOIN_CASH: BitcoinCashAdapter = BitcoinCashAdapter::new(BitcoinAdapter::new(
+    CoinType::BitcoinCash,
+    145,
+    wallet_bitcoin::BITCOIN_CASH,
+));
+static ETHEREUM: EthereumAdapter = EthereumAdapter;
+static SOLANA: SolanaAdapter = SolanaAdapter;
+static COSMOS: CosmosAdapter = CosmosAdapter::new(CoinType::Cosmos, 118, "cosmos");
+static OSMOSIS: CosmosAdapter = CosmosAdapter::new(CoinType::Osmosis, 118, "osmo");
+static JUNO: CosmosAdapter = CosmosAdapter::new(CoinType::Juno, 118, "juno");
+static TRON: TronAdapter = TronAdapter;
+
+/// Every coin the wallet supports
+pub const COINS: &[CoinType] = &[
+    CoinType::Bitcoin,
+    CoinType::BitcoinTestnet,
+    CoinType::BitcoinSignet,
+    CoinType::BitcoinRegtest,
+    CoinType::Litecoin,
+    CoinType::Dogecoin,
+    CoinType::Ethereum,
+    CoinType::Solana,
+    CoinType::Cosmos,
+    CoinType::Osmosis,
+    CoinType::Juno,
+    CoinType::Tron,
+    CoinType::BitcoinCash,
+];
+
+/// The registry of built-in adapters, the wallet looks up everything chain specific here.
+pub fn adapter(coin: CoinType) -> &'static dyn CoinAdapter {
+    match coin {
+        CoinType::Bitcoin => &BITCOIN,
+        CoinType::BitcoinTestnet => &BITCOIN_TESTNET,
+        CoinType::BitcoinSignet => &BITCOIN_SIGNET,
+        CoinType::BitcoinRegtest => &BITCOIN_REGTEST,
+        CoinType::Litecoin => &LITECOIN,
+        CoinType::Dogecoin => &DOGECOIN,
+        CoinType::Ethereum => &ETHEREUM,
+        CoinType::Solana => &SOLANA,
+        CoinType::Cosmos => &COSMOS,
+        CoinType::Osmosis => &OSMOSIS,
+        CoinType::Juno => &JUNO,
+        CoinType::Tron => &TRON,
+        CoinType::BitcoinCash => &BITCOIN_CASH,
+    }
+}
+
+/// Coins `address` is valid for. Testnets share encodings, so there can be more than one.
+pub fn coins_for_address(address: &str) -> Vec<CoinType> {
+    COINS
+        .iter()
+        .copied()
+        .filter(|coin| adapter(*coin).validate_address(address))
+        .collect()
+}
+
+/// The coin of a bitcoin `network`, e.g. to pick regtest from a node's configuration.
+pub fn bitcoin_coin(network: Network) -> Option<CoinType> {
+    COINS
+        .iter()
+        .copied()
+        .find(|coin| adapter(*coin).bitcoin_network() == Some(network))
+}
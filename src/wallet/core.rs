@@ -1,51 +1,100 @@
-use super::evm::{address_from_pubkey, to_checksum_address, uncompress_pub_key};
-use super::wallet_bitcoin::address_from_compressed_pub_key;
+use super::coins::{self, CoinAdapter, Curve};
+use super::discovery::{scan_chain, AddressHistory};
+use super::encryption::{EncryptedFile, FileKey, SealedTree, TreeKey};
+use super::export::{parse_xpub, private_prefix, public_prefix, AccountExport};
+use super::labels::{
+    descriptor_origin, parse_labels, write_labels, Annotations, LabelRecord, LabelType,
+};
+use super::lookup::{AddressIndex, AddressLocation};
+use super::path::{DerivationPath, Purpose, MAX_CHILD_INDEX};
+use super::slip10::Ed25519Key;
+use super::wallet_bitcoin::x_only_pub_key;
 use anyhow::{Error, Result};
 use bip32::{
     secp256k1::ecdsa::{SigningKey, VerifyingKey},
-    ExtendedPrivateKey, ExtendedPublicKey, Language, Mnemonic, PublicKey, Seed, XPrv,
+    ExtendedPrivateKey, ExtendedPublicKey, Prefix, PublicKey, Seed, XPrv, XPub,
 };
-use hex::ToHex;
-use rand::rngs::OsRng;
-use serde::{Deserialize, Serialize};
+use bip39::{Language, Mnemonic};
+use bitcoin::bip32::{ChildNumber, ExtendedPubKey};
+use bitcoin::secp256k1::{Secp256k1, VerifyOnly};
+use rand::{rngs::OsRng, RngCore};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use std::{
     cmp::Eq,
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     fmt,
     fs::OpenOptions,
     hash::Hash,
-    io::{BufReader, BufWriter},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
 };
 
 // ERR MESSAGES
-const INVALID_BIP44_PATH_FORMT: &str = "invalid bip44 path format";
-const UNHARDENED_KEY: &str = "must harden child key";
-const INVALID_WALLET_PATH: &str = "invalid wallet path for current state";
+const WALLET_LOCKED: &str = "wallet has no password set, cannot save";
+const INVALID_WORD_COUNT: &str = "mnemonic word count must be 12, 15, 18, 21 or 24";
+const WORD_COUNT_MISMATCH: &str = "stored mnemonic entropy does not match its word count";
+const UNSUPPORTED_PURPOSE: &str = "purpose is not supported for this coin";
+const INVALID_GAP_LIMIT: &str = "gap limit must be at least 1";
+const UNKNOWN_ACCOUNT: &str = "account does not exist";
+const UNKNOWN_ADDRESS: &str = "address does not belong to this wallet";
+const ADDRESS_INDEX_OVERFLOW: &str = "no address indexes left in this chain";
+const NOT_UNLOCKED: &str = "wallet is not unlocked";
+const WATCH_ONLY: &str = "watch-only wallet has no private keys";
+const NOT_AN_ACCOUNT_XPUB: &str =
+    "extended public key must be at account level, i.e. m/purpose'/coin'/account'";
+const NO_EXTENDED_KEYS: &str = "ed25519 accounts have no extended keys";
+
+/// Word count used for new wallets unless told otherwise
+pub const DEFAULT_WORD_COUNT: usize = 24;
 
 // TODO change this path
 const WALLET_FILE_PATH: &str = "crypto_wallet.json";
+// appended to the wallet path while a save is in progress
+const TEMP_FILE_SUFFIX: &str = ".tmp";
 
+/// The SLIP-44 index, as used in derivation paths
 impl fmt::Display for CoinType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            CoinType::Bitcoin => write!(f, "0"),
-            CoinType::BitcoinTestnet => write!(f, "1"),
-            CoinType::Ethereum => write!(f, "60"),
-        }
+        write!(f, "{}", self.slip44())
     }
 }
 
-/// BIP-44 compliant enum for major coin types for the wallet
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+/// BIP-44 compliant enum for major coin types for the wallet.
+///
+/// Only identifies the coin, everything chain specific lives in its `CoinAdapter`.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub enum CoinType {
-    // secp256k1
-    // need find address algorithm
-    Bitcoin = 0,
-    // probably the same
-    // check if address is different
-    BitcoinTestnet = 1,
-    // you've already done these
-    Ethereum = 60,
+    Bitcoin,
+    BitcoinTestnet,
+    BitcoinSignet,
+    BitcoinRegtest,
+    Litecoin,
+    Dogecoin,
+    Ethereum,
+    Solana,
+    Cosmos,
+    Osmosis,
+    Juno,
+    Tron,
+    BitcoinCash,
+}
+
+impl CoinType {
+    pub fn adapter(&self) -> &'static dyn CoinAdapter {
+        coins::adapter(*self)
+    }
+
+    /// SLIP-44 coin type, shared by test networks of the same chain
+    pub fn slip44(&self) -> u32 {
+        self.adapter().slip44()
+    }
+
+    /// Whether accounts of this coin can be created under `purpose`. Segwit purposes only
+    /// make sense for bitcoin.
+    pub fn supports_purpose(&self, purpose: Purpose) -> bool {
+        self.adapter().purposes().contains(&purpose)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -54,6 +103,11 @@ pub struct Bip44Account {
     // We include a string so that you can
     pub name: String,
     pub changes: HashMap<Bip44ChangeVal, Bip44Change>,
+    /// Account level extended public key, addresses of watch-only accounts are derived from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xpub: Option<String>,
+    #[serde(default, skip_serializing_if = "Annotations::is_empty")]
+    pub annotations: Annotations,
 }
 
 impl fmt::Display for Bip44ChangeVal {
@@ -66,12 +120,35 @@ impl fmt::Display for Bip44ChangeVal {
 }
 
 /// 0 for receiving address, 1 for internal address. all will be recieivng for now.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Bip44ChangeVal {
     RECEIVING,
     INTERNAL,
 }
 
+impl Bip44ChangeVal {
+    /// Index of the change level in a derivation path
+    pub fn index(&self) -> u32 {
+        match self {
+            Bip44ChangeVal::RECEIVING => 0,
+            Bip44ChangeVal::INTERNAL => 1,
+        }
+    }
+}
+
+/// Which serialisation of the public key was hashed into an address.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum PubKeyForm {
+    Compressed,
+    // ethereum addresses, and bitcoin P2PKH addresses from earlier versions of this wallet
+    Uncompressed,
+}
+
+// addresses stored before the key form was recorded all hashed the uncompressed key
+fn legacy_key_form() -> PubKeyForm {
+    PubKeyForm::Uncompressed
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Bip44Change {
     pub change: Bip44ChangeVal,
@@ -79,14 +156,34 @@ pub struct Bip44Change {
     pub addresses: HashMap<u32, Bip44Address>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Bip44Address {
-    pub path: String,
+    pub path: DerivationPath,
     // just using vec for heap storage. Don't push or pop.
     // TODO serialize the bytes properly so you don't get runtime bugs out to hell
     pub pub_key: Vec<u8>,
     pub address: String,
     pub address_checksummed: String,
+    #[serde(default = "legacy_key_form")]
+    pub key_form: PubKeyForm,
+    /// Untweaked x-only internal key of taproot (BIP86) addresses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x_only_pub_key: Option<Vec<u8>>,
+    /// Set when the address was re-derived from a path with the old, non-standard layout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legacy: Option<LegacyAddress>,
+    /// BIP329 label, e.g. who the address was handed out to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Annotations::is_empty")]
+    pub annotations: Annotations,
+}
+
+/// Path and address an entry had before `Wallet::migrate_legacy_paths` fixed its layout.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LegacyAddress {
+    pub path: String,
+    pub address: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -96,47 +193,463 @@ pub struct Accounts {
     pub accounts: HashMap<u32, Bip44Account>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Accounts {
+    fn new(purpose: Purpose, coin: CoinType) -> Self {
+        Accounts {
+            path: format!("m/{}'/{}'", purpose, coin),
+            next_index: 0,
+            accounts: HashMap::new(),
+        }
+    }
+}
+
+/// Accounts of one coin, one `Accounts` per purpose so that e.g. BIP44 and BIP84 account 0
+/// live side by side like they do in other wallets.
+pub type CoinAccounts = HashMap<Purpose, Accounts>;
+
+/// Files written before purposes were selectable stored a single BIP44 `Accounts` per coin.
+///
+/// Goes through `serde_json::Value` because the untagged enum route cannot read the numeric
+/// map keys of `Accounts`.
+struct StoredCoinAccounts(CoinAccounts);
+
+impl<'de> Deserialize<'de> for StoredCoinAccounts {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let accounts = if value.get("path").is_some() {
+            serde_json::from_value::<Accounts>(value)
+                .map(|accounts| HashMap::from([(Purpose::Bip44, accounts)]))
+        } else {
+            serde_json::from_value::<CoinAccounts>(value)
+        };
+        accounts.map(StoredCoinAccounts).map_err(de::Error::custom)
+    }
+}
+
+fn deserialize_coins<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<CoinType, CoinAccounts>, D::Error> {
+    let stored = HashMap::<CoinType, StoredCoinAccounts>::deserialize(deserializer)?;
+    Ok(stored
+        .into_iter()
+        .map(|(coin, accounts)| (coin, accounts.0))
+        .collect())
+}
+
+/// The tree a wallet file opens without a passphrase, set aside while a hidden wallet is unlocked
+/// and written back in its place when saving. Never serialized.
+#[derive(Debug)]
+struct PlainTree {
+    master_fingerprint: String,
+    coins: HashMap<CoinType, CoinAccounts>,
+    // seals the unlocked hidden tree
+    hidden_key: TreeKey,
+}
+
+/// What `Wallet::save_to_file` encrypts: the plain tree readable and the hidden ones sealed.
+#[derive(Serialize)]
+struct StoredWallet<'a> {
+    mnemonic: &'a [u8],
+    word_count: usize,
+    master_fingerprint: &'a str,
+    coins: &'a HashMap<CoinType, CoinAccounts>,
+    hidden_wallets: Vec<SealedTree>,
+    watch_only: bool,
+}
+
+/// Keys derived while the wallet is unlocked so that addresses don't have to be derived from
+/// the root every time. Never serialized.
+struct SessionKeys {
+    seed: Option<Seed>,
+    account_keys: HashMap<(CoinType, Purpose, u32), XPub>,
+    // libsecp256k1 derives public children several times faster than the pure Rust bip32 crate
+    chain_keys: HashMap<(CoinType, Purpose, u32, Bip44ChangeVal), ExtendedPubKey>,
+    secp: Secp256k1<VerifyOnly>,
+}
+
+impl SessionKeys {
+    fn new(seed: Option<Seed>) -> Self {
+        SessionKeys {
+            seed,
+            account_keys: HashMap::new(),
+            chain_keys: HashMap::new(),
+            secp: Secp256k1::verification_only(),
+        }
+    }
+
+    /// The `m/purpose'/coin'/account'` public key, derived from the seed or parsed from the
+    /// watch-only `xpub` the first time it is needed.
+    fn account_key(
+        &mut self,
+        coin: CoinType,
+        purpose: Purpose,
+        account: u32,
+        xpub: Option<&str>,
+    ) -> Result<XPub> {
+        if let Some(key) = self.account_keys.get(&(coin, purpose, account)) {
+            return Ok(key.clone());
+        }
+        let key = match (xpub, &self.seed) {
+            (Some(xpub), _) => parse_xpub(xpub)?,
+            (None, Some(seed)) => {
+                let path = DerivationPath::for_account(purpose, coin.slip44(), account)?;
+                derive_child(seed, &path)?.pub_key
+            }
+            (None, None) => return Err(Error::msg(WATCH_ONLY)),
+        };
+        self.account_keys
+            .insert((coin, purpose, account), key.clone());
+        Ok(key)
+    }
+
+    /// Public key of address `index`, compressed for secp256k1 coins where it is derived from the
+    /// cached key of its chain. ed25519 keys are derived from the seed.
+    fn address_key(
+        &mut self,
+        coin: CoinType,
+        purpose: Purpose,
+        account: u32,
+        change: Bip44ChangeVal,
+        index: u32,
+        xpub: Option<&str>,
+    ) -> Result<Vec<u8>> {
+        if coin.adapter().curve() == Curve::Ed25519 {
+            let seed = self.seed.as_ref().ok_or(Error::msg(WATCH_ONLY))?;
+            let path = address_path(purpose, coin, account, &change, index)?;
+            let key = Ed25519Key::derive_from_path(seed.as_bytes(), &path)?;
+            return Ok(key.public_key().to_vec());
+        }
+        let chain = (coin, purpose, account, change);
+        if !self.chain_keys.contains_key(&chain) {
+            let account_key = self.account_key(coin, purpose, account, xpub)?;
+            let account_key = ExtendedPubKey::from_str(&account_key.to_string(Prefix::XPUB))?;
+            let chain_key =
+                account_key.ckd_pub(&self.secp, ChildNumber::from_normal_idx(change.index())?)?;
+            self.chain_keys.insert(chain, chain_key);
+        }
+        let key =
+            self.chain_keys[&chain].ckd_pub(&self.secp, ChildNumber::from_normal_idx(index)?)?;
+        Ok(key.public_key.serialize().to_vec())
+    }
+}
+
+impl Default for SessionKeys {
+    fn default() -> Self {
+        SessionKeys::new(None)
+    }
+}
+
+impl fmt::Debug for SessionKeys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SessionKeys")
+            .field("account_keys", &self.account_keys.len())
+            .field("chain_keys", &self.chain_keys.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Deserialize, Debug)]
 pub struct Wallet {
-    pub mnemonic: [u8; 32],
-    pub coins: HashMap<CoinType, Accounts>,
+    /// BIP39 entropy, 16 to 32 bytes depending on `word_count`. Empty for watch-only wallets.
+    pub mnemonic: Vec<u8>,
+    // files written before variable length mnemonics were supported are always 24 words
+    #[serde(default = "default_word_count")]
+    pub word_count: usize,
+    /// Hex master key fingerprint of the tree in `coins`, depends on the BIP39 passphrase
+    #[serde(default)]
+    pub master_fingerprint: String,
+    #[serde(deserialize_with = "deserialize_coins")]
+    pub coins: HashMap<CoinType, CoinAccounts>,
+    /// Account trees of the other passphrases used with this mnemonic, each sealed under a key
+    /// derived from its own seed. The unlocked one is in `coins`.
+    #[serde(default)]
+    pub hidden_wallets: Vec<SealedTree>,
+    /// Set for wallets built from an account xpub, see `Wallet::watch_only`
+    #[serde(default)]
+    pub watch_only: bool,
+    // the BIP39 passphrase is never stored, only the seed derived from it is held in memory
+    #[serde(skip)]
+    session: SessionKeys,
+    // set while a hidden wallet is unlocked
+    #[serde(skip)]
+    plain: Option<PlainTree>,
+    // rebuilt from `coins` whenever the wallet is loaded
+    #[serde(skip)]
+    addresses: AddressIndex,
+    // where the wallet was loaded from and the key to re-encrypt it with, never serialized
+    #[serde(skip)]
+    file_path: PathBuf,
+    #[serde(skip)]
+    file_key: Option<FileKey>,
 }
 
 impl Wallet {
-    pub fn new() -> Result<Self> {
-        let mnemonic = gen_mnemonic();
-        let new_wallet = Wallet {
-            mnemonic: *mnemonic.entropy(),
+    /// Creates a wallet with a fresh `word_count` word mnemonic and saves it, encrypted with
+    /// `password`, to the default wallet file.
+    ///
+    /// `passphrase` is the optional BIP39 passphrase ("25th word"). It is never written to disk
+    /// and every different passphrase opens a different hidden wallet.
+    pub fn new(word_count: usize, password: &str, passphrase: Option<&str>) -> Result<Self> {
+        Wallet::new_at(WALLET_FILE_PATH, word_count, password, passphrase)
+    }
+
+    /// Same as `Wallet::new` but saves to `path`.
+    pub fn new_at(
+        path: impl AsRef<Path>,
+        word_count: usize,
+        password: &str,
+        passphrase: Option<&str>,
+    ) -> Result<Self> {
+        let mnemonic = gen_mnemonic(word_count)?;
+        Wallet::from_entropy_at(path, mnemonic.to_entropy(), password, passphrase)
+    }
+
+    /// Restores a wallet from an existing English BIP39 `phrase` and saves it, encrypted with
+    /// `password`, to the default wallet file.
+    ///
+    /// The phrase is checked against the BIP39 word list and its checksum before anything is
+    /// written, so a typo never overwrites the current wallet file.
+    pub fn from_mnemonic(phrase: &str, password: &str, passphrase: Option<&str>) -> Result<Self> {
+        Wallet::from_mnemonic_at(WALLET_FILE_PATH, phrase, password, passphrase)
+    }
+
+    /// Same as `Wallet::from_mnemonic` but saves to `path`.
+    pub fn from_mnemonic_at(
+        path: impl AsRef<Path>,
+        phrase: &str,
+        password: &str,
+        passphrase: Option<&str>,
+    ) -> Result<Self> {
+        let entropy = parse_mnemonic(phrase)?;
+        Wallet::from_entropy_at(path, entropy, password, passphrase)
+    }
+
+    fn from_entropy_at(
+        path: impl AsRef<Path>,
+        entropy: Vec<u8>,
+        password: &str,
+        passphrase: Option<&str>,
+    ) -> Result<Self> {
+        let mut new_wallet = Wallet {
+            word_count: word_count_for_entropy(entropy.len())?,
+            mnemonic: entropy,
+            master_fingerprint: String::new(),
             coins: HashMap::new(),
+            hidden_wallets: Vec::new(),
+            watch_only: false,
+            session: SessionKeys::default(),
+            plain: None,
+            addresses: AddressIndex::default(),
+            file_path: path.as_ref().to_path_buf(),
+            file_key: Some(FileKey::new(password)?),
         };
+        new_wallet.unlock(passphrase)?;
         new_wallet.save_to_file()?;
         Ok(new_wallet)
     }
 
-    fn save_to_file(&self) -> Result<()> {
-        // TODO password encryption
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(WALLET_FILE_PATH)?;
-        let buf_writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(buf_writer, self)?;
-        Ok(())
+    /// Creates a watch-only wallet from an account level extended public key and saves it,
+    /// encrypted with `password`, to the default wallet file.
+    ///
+    /// Any public SLIP-132 version (`xpub`, `ypub`, `zpub`, `tpub`...) is accepted, the address
+    /// type follows `purpose`. The account index is read from the key itself. The wallet can
+    /// generate receive and change addresses but everything needing the seed fails.
+    pub fn watch_only(
+        xpub: &str,
+        coin: CoinType,
+        purpose: Purpose,
+        account_name: &str,
+        password: &str,
+    ) -> Result<Self> {
+        Wallet::watch_only_at(
+            WALLET_FILE_PATH,
+            xpub,
+            coin,
+            purpose,
+            account_name,
+            password,
+        )
+    }
+
+    /// Same as `Wallet::watch_only` but saves to `path`.
+    pub fn watch_only_at(
+        path: impl AsRef<Path>,
+        xpub: &str,
+        coin: CoinType,
+        purpose: Purpose,
+        account_name: &str,
+        password: &str,
+    ) -> Result<Self> {
+        if !coin.supports_purpose(purpose) {
+            return Err(Error::msg(UNSUPPORTED_PURPOSE));
+        }
+        if coin.adapter().curve() != Curve::Secp256k1 {
+            return Err(Error::msg(NO_EXTENDED_KEYS));
+        }
+        let account_index = parse_account_xpub(xpub)?;
+
+        let mut accounts = Accounts::new(purpose, coin);
+        accounts.next_index = account_index;
+        new_account(&mut accounts, account_name)?;
+        if let Some(account) = accounts.accounts.get_mut(&account_index) {
+            account.xpub = Some(xpub.trim().to_string());
+        }
+
+        let wallet = Wallet {
+            mnemonic: Vec::new(),
+            word_count: 0,
+            master_fingerprint: String::new(),
+            coins: HashMap::from([(coin, HashMap::from([(purpose, accounts)]))]),
+            hidden_wallets: Vec::new(),
+            watch_only: true,
+            session: SessionKeys::default(),
+            plain: None,
+            addresses: AddressIndex::default(),
+            file_path: path.as_ref().to_path_buf(),
+            file_key: Some(FileKey::new(password)?),
+        };
+        wallet.save_to_file()?;
+        Ok(wallet)
+    }
+
+    /// Encrypts and writes the wallet back to the file it was created at or loaded from.
+    ///
+    /// The file is written next to the old one and renamed over it once it is on disk, so a
+    /// crash or a full disk leaves either the old or the new wallet, never a truncated one.
+    pub fn save_to_file(&self) -> Result<()> {
+        let file_key = self.file_key.as_ref().ok_or(Error::msg(WALLET_LOCKED))?;
+        let plaintext = serde_json::to_vec(&self.stored()?)?;
+        let encrypted: EncryptedFile = file_key.seal(&plaintext)?;
+
+        let mut temp_path = self.file_path.clone().into_os_string();
+        temp_path.push(TEMP_FILE_SUFFIX);
+        let temp_path = PathBuf::from(temp_path);
+        let written = write_synced(&temp_path, &encrypted)
+            .and_then(|()| std::fs::rename(&temp_path, &self.file_path).map_err(Error::from));
+        if written.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        written?;
+        sync_parent_dir(&self.file_path)
+    }
+
+    // an empty hidden tree is not sealed, so a mistyped passphrase leaves nothing behind
+    fn stored(&self) -> Result<StoredWallet<'_>> {
+        let mut hidden_wallets = self.hidden_wallets.clone();
+        let (master_fingerprint, coins) = match &self.plain {
+            Some(plain) => {
+                if !self.coins.is_empty() {
+                    hidden_wallets.push(plain.hidden_key.seal(&serde_json::to_vec(&self.coins)?)?);
+                }
+                (&plain.master_fingerprint, &plain.coins)
+            }
+            None => (&self.master_fingerprint, &self.coins),
+        };
+        Ok(StoredWallet {
+            mnemonic: &self.mnemonic,
+            word_count: self.word_count,
+            master_fingerprint,
+            coins,
+            hidden_wallets,
+            watch_only: self.watch_only,
+        })
+    }
+
+    /// Loads and decrypts the default wallet file.
+    ///
+    /// Fails with `encryption::INCORRECT_PASSWORD` for a wrong password and
+    /// `encryption::TAMPERED_WALLET_FILE` if the file was modified after it was written.
+    ///
+    /// `passphrase` selects which hidden wallet is opened, see `Wallet::new`. It is ignored for
+    /// watch-only wallets.
+    ///
+    /// Files written before the wallet was encrypted are read as they are and saved again,
+    /// encrypted with `password`.
+    pub fn from_file(password: &str, passphrase: Option<&str>) -> Result<Self> {
+        Wallet::from_path(WALLET_FILE_PATH, password, passphrase)
     }
 
-    pub fn from_file() -> Result<Self> {
-        // TODO password encryption
-        let file = OpenOptions::new().read(true).open(WALLET_FILE_PATH)?;
+    /// Same as `Wallet::from_file` but reads from `path`.
+    pub fn from_path(
+        path: impl AsRef<Path>,
+        password: &str,
+        passphrase: Option<&str>,
+    ) -> Result<Self> {
+        let file = OpenOptions::new().read(true).open(path.as_ref())?;
         let buf_reader: BufReader<std::fs::File> = BufReader::new(file);
-        let wallet: Wallet = serde_json::from_reader(buf_reader)?;
+        let contents: serde_json::Value = serde_json::from_reader(buf_reader)?;
+        let unencrypted = is_unencrypted_wallet(&contents);
+        let (plaintext, file_key) = if unencrypted {
+            (serde_json::to_vec(&contents)?, FileKey::new(password)?)
+        } else {
+            serde_json::from_value::<EncryptedFile>(contents)?.open(password)?
+        };
+
+        let mut wallet: Wallet = serde_json::from_slice(&plaintext)?;
+        wallet.file_path = path.as_ref().to_path_buf();
+        wallet.file_key = Some(file_key);
+        let mut changed = unencrypted;
+        if !wallet.watch_only {
+            if word_count_for_entropy(wallet.mnemonic.len())? != wallet.word_count {
+                return Err(Error::msg(WORD_COUNT_MISMATCH));
+            }
+            wallet.unlock(passphrase)?;
+            changed |= wallet.migrate_legacy_paths()? > 0;
+        }
+        changed |= wallet.repair_evm_addresses() > 0;
+        if changed {
+            wallet.save_to_file()?;
+        }
+        wallet.reindex_addresses();
         Ok(wallet)
     }
 
+    /// Switches `coins` to the account tree belonging to `passphrase`.
+    ///
+    /// The tree stored in the clear is the one without a passphrase. Any other passphrase opens
+    /// the hidden tree sealed under its seed, or an empty one the first time it is used.
+    fn unlock(&mut self, passphrase: Option<&str>) -> Result<()> {
+        if self.master_fingerprint.is_empty() {
+            // trees saved before passphrases were supported were derived without one
+            let seed = mnemonic_as_seed(&self.mnemonic, None)?;
+            self.master_fingerprint = master_fingerprint(&seed)?;
+        }
+        let seed = mnemonic_as_seed(&self.mnemonic, passphrase)?;
+        let fingerprint = master_fingerprint(&seed)?;
+
+        if fingerprint != self.master_fingerprint {
+            let hidden_key = TreeKey::from_seed(seed.as_bytes())?;
+            let opened = self
+                .hidden_wallets
+                .iter()
+                .enumerate()
+                .find_map(|(position, tree)| Some((position, hidden_key.open(tree)?)));
+            let hidden = match opened {
+                Some((position, plaintext)) => {
+                    self.hidden_wallets.remove(position);
+                    // trees sealed before purposes were selectable hold one `Accounts` per coin
+                    deserialize_coins(&mut serde_json::Deserializer::from_slice(&plaintext))?
+                }
+                None => HashMap::new(),
+            };
+            self.plain = Some(PlainTree {
+                master_fingerprint: std::mem::replace(&mut self.master_fingerprint, fingerprint),
+                coins: std::mem::replace(&mut self.coins, hidden),
+                hidden_key,
+            });
+        }
+        // account keys of the previous tree must not be reused
+        self.session = SessionKeys::new(Some(seed));
+        Ok(())
+    }
+
     pub fn show_mnemonic(&self) -> Result<String> {
-        let wallet: Wallet = Wallet::from_file()?;
+        if self.watch_only {
+            return Err(Error::msg(WATCH_ONLY));
+        }
         // English is currently the only supported language
-        let mnemnonic: Mnemonic = Mnemonic::from_entropy(wallet.mnemonic, Language::English);
-        Ok(mnemnonic.phrase().to_string())
+        let mnemnonic: Mnemonic = Mnemonic::from_entropy_in(Language::English, &self.mnemonic)?;
+        Ok(mnemnonic.to_string())
     }
 
     /// Creates a new account for a given `CoinType` and associates it with an account name.
@@ -145,12 +658,16 @@ impl Wallet {
     /// with a provided account name. If an account with the given `CoinType` already exists, the
     /// new account will be added to the existing ones.
     ///
-    /// Follows bip44 specification.
+    /// Follows bip44 specification. Account indexes are counted per purpose, so the first BIP84
+    /// account is `m/84'/0'/0'` even if BIP44 accounts already exist.
     ///
     ///
     /// # Arguments
     ///
     /// - `coin`: A `CoinType` representing the type of cryptocurrency for the new account.
+    /// - `purpose`: The derivation scheme of the account, `Purpose::Bip49` gives nested segwit,
+    ///   `Purpose::Bip84` native segwit and `Purpose::Bip86` taproot bitcoin addresses. Every
+    ///   coin supports `Purpose::Bip44`.
     /// - `account_name`: A string containing the name for the new account.
     ///
     /// # Returns
@@ -161,14 +678,16 @@ impl Wallet {
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
+    /// use cryptowallet::wallet::core::{CoinType, Wallet};
+    /// use cryptowallet::wallet::path::Purpose;
     ///
-    /// let mut wallet = Wallet::new(); // Create an instance of your HD wallet
+    /// let mut wallet = Wallet::new(24, "password", None).unwrap(); // Create an instance of your HD wallet
     ///
     /// let coin_type = CoinType::Bitcoin; // Define the coin type
     /// let account_name = "Savings"; // Define the account name
     ///
-    /// match wallet.new_account(coin_type, account_name) {
+    /// match wallet.new_account(coin_type, Purpose::Bip84, account_name) {
     ///     Ok(account_index) => {
     ///         println!("New account created with index: {}", account_index);
     ///     }
@@ -176,22 +695,27 @@ impl Wallet {
     ///         eprintln!("Error creating a new account: {}", error);
     ///     }
     /// }
+    /// ```
     ///
     /// TODO work out whether you want to have internal accounts
-    ///
-    /// ```
-    pub fn new_account(&mut self, coin: CoinType, account_name: &str) -> Result<u32> {
-        //
-        let accounts_entry = self.coins.entry(coin);
+    pub fn new_account(
+        &mut self,
+        coin: CoinType,
+        purpose: Purpose,
+        account_name: &str,
+    ) -> Result<u32> {
+        // new accounts need the seed to derive their keys
+        if self.watch_only {
+            return Err(Error::msg(WATCH_ONLY));
+        }
+        if !coin.supports_purpose(purpose) {
+            return Err(Error::msg(UNSUPPORTED_PURPOSE));
+        }
+        let accounts_entry = self.coins.entry(coin).or_default().entry(purpose);
 
         match accounts_entry {
             Entry::Vacant(vacant) => {
-                let entry = Accounts {
-                    path: format!("m/44'/{}", coin),
-                    next_index: 0,
-                    accounts: HashMap::new(),
-                };
-                let accounts = vacant.insert(entry);
+                let accounts = vacant.insert(Accounts::new(purpose, coin));
 
                 new_account(accounts, account_name)
             }
@@ -202,101 +726,772 @@ impl Wallet {
         }
     }
 
+    /// Adds the next address of the chain selected by `prms`. The wallet file is not written,
+    /// call `Wallet::save_to_file` or use `Wallet::new_addresses` for that.
     pub fn new_address(&mut self, prms: NewAddressParams) -> Result<()> {
-        if let Some(coin_accts) = self.coins.get_mut(&prms.coin) {
-            if let Some(acct) = coin_accts.accounts.get_mut(&prms.account) {
-                if let Some(change) = acct.changes.get_mut(&prms.change) {
-                    let index = change.next_address_index;
-                    let path: &str = &format!(
-                        "m/44'/{}/{}/{}/{}",
-                        prms.coin, prms.change, prms.account, index
-                    );
-                    validate_bip_44_path(path)?;
-                    let seed = mnemonic_as_seed(self.mnemonic, None);
-                    let key_pair = derive_child(&seed, path)?;
-
-                    let key_bytes: [u8; 33] = key_pair.pub_key.public_key().to_bytes();
-                    let uncompressed_pub_key: [u8; 65] = uncompress_pub_key(key_bytes);
-
-                    match prms.coin {
-                        CoinType::Bitcoin => {
-                            let address = address_from_compressed_pub_key(
-                                key_bytes,
-                                bitcoin::Network::Bitcoin,
-                            )?;
-                            let address_data = Bip44Address {
-                                path: path.to_string(),
-                                pub_key: key_bytes.to_vec(),
-                                address: address.to_string(),
-                                // unlike ethereum, bitcoin addresses are checksummed by default
-                                address_checksummed: address.to_string(),
-                            };
-
-                            change.next_address_index += 1;
-                            change.addresses.insert(index, address_data);
-                        }
-                        CoinType::BitcoinTestnet => {
-                            //
-                            //
-                            // Sorry about this I just wanted to finish. It's just a copy of the code for Bitcoin network with an enum changed ^^^^
-                            //
-                            //
-                            let address = address_from_compressed_pub_key(
-                                key_bytes,
-                                bitcoin::Network::Testnet,
-                            )?;
-                            let address_data = Bip44Address {
-                                path: path.to_string(),
-                                pub_key: key_bytes.to_vec(),
-                                address: address.to_string(),
-                                // unlike ethereum, bitcoin addresses are checksummed by default
-                                address_checksummed: address.to_string(),
-                            };
-
-                            change.next_address_index += 1;
-                            change.addresses.insert(index, address_data);
-                        }
-                        CoinType::Ethereum => {
-                            let address: web3::types::H160 =
-                                address_from_pubkey(uncompressed_pub_key);
-                            let address_checksummed: String = to_checksum_address(&address);
-
-                            let address_data = Bip44Address {
-                                path: path.to_string(),
-                                pub_key: key_bytes.to_vec(),
-                                address: address.to_string(),
-                                address_checksummed,
-                            };
-
-                            change.next_address_index += 1;
-                            change.addresses.insert(index, address_data);
+        self.derive_next_addresses(&prms, 1)?;
+        Ok(())
+    }
+
+    /// Adds the next `count` addresses of the chain selected by `prms` and writes the wallet file
+    /// once for the whole batch.
+    ///
+    /// Returns the new addresses in index order. Nothing is added if any of them fails to derive.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use cryptowallet::wallet::core::{CoinType, NewAddressParams, Wallet};
+    /// use cryptowallet::wallet::path::Purpose;
+    ///
+    /// let mut wallet = Wallet::from_file("password", None).unwrap();
+    /// let prms = NewAddressParams::new(CoinType::Bitcoin, 0).purpose(Purpose::Bip84);
+    /// for address in wallet.new_addresses(prms, 500).unwrap() {
+    ///     println!("{} {}", address.path, address.address);
+    /// }
+    /// ```
+    pub fn new_addresses(
+        &mut self,
+        prms: NewAddressParams,
+        count: u32,
+    ) -> Result<Vec<Bip44Address>> {
+        let addresses = self.derive_next_addresses(&prms, count)?;
+        if !addresses.is_empty() {
+            self.save_to_file()?;
+        }
+        Ok(addresses)
+    }
+
+    fn derive_next_addresses(
+        &mut self,
+        prms: &NewAddressParams,
+        count: u32,
+    ) -> Result<Vec<Bip44Address>> {
+        let acct = self
+            .coins
+            .get_mut(&prms.coin)
+            .and_then(|purposes| purposes.get_mut(&prms.purpose))
+            .and_then(|accounts| accounts.accounts.get_mut(&prms.account))
+            .ok_or(Error::msg(UNKNOWN_ACCOUNT))?;
+        let change = acct
+            .changes
+            .get_mut(&prms.change)
+            .ok_or(Error::msg(UNKNOWN_ACCOUNT))?;
+
+        let first = change.next_address_index;
+        let last = first
+            .checked_add(count)
+            .ok_or(Error::msg(ADDRESS_INDEX_OVERFLOW))?;
+        let per_chain = prms.coin.adapter().addresses_per_chain();
+        if last > per_chain {
+            // the adapter knows why the chain is full, e.g. solana accounts have one address
+            address_path(
+                prms.purpose,
+                prms.coin,
+                prms.account,
+                &prms.change,
+                per_chain.max(first),
+            )?;
+            return Err(Error::msg(ADDRESS_INDEX_OVERFLOW));
+        }
+        let addresses = (first..last)
+            .map(|index| {
+                let path =
+                    address_path(prms.purpose, prms.coin, prms.account, &prms.change, index)?;
+                let key = self.session.address_key(
+                    prms.coin,
+                    prms.purpose,
+                    prms.account,
+                    prms.change,
+                    index,
+                    acct.xpub.as_deref(),
+                )?;
+                address_from_pub_key(prms.coin, &path, &key, PubKeyForm::Compressed)
+            })
+            .collect::<Result<Vec<Bip44Address>>>()?;
+
+        for (index, address) in (first..last).zip(&addresses) {
+            change.addresses.insert(index, address.clone());
+            let location = AddressLocation {
+                coin: prms.coin,
+                purpose: prms.purpose,
+                account: prms.account,
+                change: prms.change,
+                index,
+                path: address.path.clone(),
+                legacy: false,
+            };
+            self.addresses.insert(address, location);
+        }
+        change.next_address_index = last;
+        Ok(addresses)
+    }
+
+    /// Finds the used accounts and addresses of `coin` under `purpose` with BIP44 account
+    /// discovery and adds them to the wallet.
+    ///
+    /// Accounts are scanned from index 0, each chain until `gap_limit` unused addresses in a
+    /// row, and scanning stops at the first account whose receiving chain was never used. Watch
+    /// only wallets only scan the accounts they hold an xpub for. Addresses already in the wallet
+    /// are kept as they are and `next_address_index` is moved past the last used address.
+    ///
+    /// Returns the indexes of the accounts that have been used.
+    pub fn discover_accounts(
+        &mut self,
+        coin: CoinType,
+        purpose: Purpose,
+        history: &dyn AddressHistory,
+        gap_limit: u32,
+    ) -> Result<Vec<u32>> {
+        if gap_limit == 0 {
+            return Err(Error::msg(INVALID_GAP_LIMIT));
+        }
+        if !coin.supports_purpose(purpose) {
+            return Err(Error::msg(UNSUPPORTED_PURPOSE));
+        }
+        let existing = self
+            .coins
+            .get(&coin)
+            .and_then(|purposes| purposes.get(&purpose));
+
+        let session = &mut self.session;
+        let mut scan_account = |account: u32, xpub: Option<&str>, change: Bip44ChangeVal| {
+            scan_chain(history, coin, gap_limit, |index| {
+                let path = address_path(purpose, coin, account, &change, index)?;
+                let key = session.address_key(coin, purpose, account, change, index, xpub)?;
+                address_from_pub_key(coin, &path, &key, PubKeyForm::Compressed)
+            })
+        };
+        let mut found = Vec::new();
+        if self.watch_only {
+            let mut xpubs: Vec<(u32, String)> = existing
+                .map(|accounts| {
+                    accounts
+                        .accounts
+                        .values()
+                        .filter_map(|acct| acct.xpub.clone().map(|xpub| (acct.index, xpub)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            xpubs.sort();
+            for (account, xpub) in xpubs {
+                let receiving = scan_account(account, Some(&xpub), Bip44ChangeVal::RECEIVING)?;
+                let internal = scan_account(account, Some(&xpub), Bip44ChangeVal::INTERNAL)?;
+                if !receiving.is_empty() || !internal.is_empty() {
+                    found.push((account, receiving, internal));
+                }
+            }
+        } else {
+            for account in 0..=MAX_CHILD_INDEX {
+                let receiving = scan_account(account, None, Bip44ChangeVal::RECEIVING)?;
+                if receiving.is_empty() {
+                    break;
+                }
+                let internal = scan_account(account, None, Bip44ChangeVal::INTERNAL)?;
+                found.push((account, receiving, internal));
+            }
+        }
+
+        let used = found.iter().map(|(account, _, _)| *account).collect();
+        if found.is_empty() {
+            return Ok(used);
+        }
+        let accounts = self
+            .coins
+            .entry(coin)
+            .or_default()
+            .entry(purpose)
+            .or_insert_with(|| Accounts::new(purpose, coin));
+        for (account, receiving, internal) in found {
+            if !accounts.accounts.contains_key(&account) {
+                let next_index = accounts.next_index.max(account + 1);
+                accounts.next_index = account;
+                new_account(accounts, &format!("Account {}", account))?;
+                accounts.next_index = next_index;
+            }
+            let acct = accounts
+                .accounts
+                .get_mut(&account)
+                .ok_or(Error::msg(UNKNOWN_ACCOUNT))?;
+            for (change, addresses) in [
+                (Bip44ChangeVal::RECEIVING, receiving),
+                (Bip44ChangeVal::INTERNAL, internal),
+            ] {
+                let chain = acct
+                    .changes
+                    .get_mut(&change)
+                    .ok_or(Error::msg(UNKNOWN_ACCOUNT))?;
+                chain.next_address_index = chain.next_address_index.max(addresses.len() as u32);
+                for (index, address) in addresses.into_iter().enumerate() {
+                    chain.addresses.entry(index as u32).or_insert(address);
+                }
+            }
+        }
+        self.reindex_addresses();
+        Ok(used)
+    }
+
+    /// Finds which coin, account, chain and index a stored address belongs to.
+    ///
+    /// Accepts the plain and the checksummed form, EVM addresses in any case. Addresses that were
+    /// migrated to a new path are also found by their old address, with `legacy` set.
+    ///
+    /// An address held by accounts on several test networks returns the first of them in coin
+    /// order, `Wallet::find_address_locations` returns all of them.
+    pub fn find_address(&self, address: &str) -> Option<&AddressLocation> {
+        self.addresses.get(address).first()
+    }
+
+    /// Every account chain and index holding `address`, see `Wallet::find_address`.
+    pub fn find_address_locations(&self, address: &str) -> &[AddressLocation] {
+        self.addresses.get(address)
+    }
+
+    /// Rebuilds the index used by `Wallet::find_address`. Only needed after changing `coins`
+    /// directly, the wallet's own methods keep it up to date.
+    pub fn reindex_addresses(&mut self) {
+        self.addresses = AddressIndex::new(&self.coins);
+    }
+
+    /// Extended public key of an account in the SLIP-132 version of its purpose (`xpub`, `ypub`,
+    /// `zpub`, `tpub`...), with the master fingerprint and origin path other wallets need to
+    /// import it.
+    pub fn export_account_xpub(
+        &self,
+        coin: CoinType,
+        purpose: Purpose,
+        account: u32,
+    ) -> Result<AccountExport> {
+        let stored = self.account(coin, purpose, account)?;
+        if coin.adapter().curve() != Curve::Secp256k1 {
+            return Err(Error::msg(NO_EXTENDED_KEYS));
+        }
+        let origin_path = DerivationPath::for_account(purpose, coin.slip44(), account)?;
+        let prefix = public_prefix(coin, purpose);
+
+        if let Some(xpub) = &stored.xpub {
+            return Ok(AccountExport {
+                extended_key: parse_xpub(xpub)?.to_string(prefix),
+                master_fingerprint: None,
+                origin_path,
+            });
+        }
+        let key_pair = derive_child(self.seed()?, &origin_path)?;
+        Ok(AccountExport {
+            extended_key: key_pair.pub_key.to_string(prefix),
+            master_fingerprint: Some(self.master_fingerprint.clone()),
+            origin_path,
+        })
+    }
+
+    /// Same as `Wallet::export_account_xpub` but exports the extended private key (`xprv`,
+    /// `yprv`, `zprv`...). Anyone holding it can spend from the account.
+    pub fn export_account_xprv(
+        &self,
+        coin: CoinType,
+        purpose: Purpose,
+        account: u32,
+    ) -> Result<AccountExport> {
+        self.account(coin, purpose, account)?;
+        if coin.adapter().curve() != Curve::Secp256k1 {
+            return Err(Error::msg(NO_EXTENDED_KEYS));
+        }
+        let origin_path = DerivationPath::for_account(purpose, coin.slip44(), account)?;
+        let key_pair = derive_child(self.seed()?, &origin_path)?;
+        Ok(AccountExport {
+            extended_key: key_pair
+                .priv_key
+                .to_string(private_prefix(coin, purpose))
+                .to_string(),
+            master_fingerprint: Some(self.master_fingerprint.clone()),
+            origin_path,
+        })
+    }
+
+    /// Renames an account, the name is exported as its BIP329 label.
+    pub fn rename_account(
+        &mut self,
+        coin: CoinType,
+        purpose: Purpose,
+        account: u32,
+        name: &str,
+    ) -> Result<()> {
+        self.account_mut(coin, purpose, account)?.name = name.to_string();
+        Ok(())
+    }
+
+    /// Edits the note, tags and archived state of an account.
+    ///
+    /// ```no_run
+    /// # use cryptowallet::wallet::core::{CoinType, Wallet};
+    /// # use cryptowallet::wallet::path::Purpose;
+    /// # let mut wallet = Wallet::from_file("password", None).unwrap();
+    /// wallet
+    ///     .annotate_account(CoinType::Bitcoin, Purpose::Bip84, 0, |annotations| {
+    ///         annotations.archived = true
+    ///     })
+    ///     .unwrap();
+    /// wallet.save_to_file().unwrap();
+    /// ```
+    pub fn annotate_account(
+        &mut self,
+        coin: CoinType,
+        purpose: Purpose,
+        account: u32,
+        edit: impl FnOnce(&mut Annotations),
+    ) -> Result<()> {
+        edit(&mut self.account_mut(coin, purpose, account)?.annotations);
+        Ok(())
+    }
+
+    /// Sets or, with `None` or an empty label, removes the label of a stored address, in every
+    /// account holding it.
+    pub fn label_address(&mut self, address: &str, label: Option<&str>) -> Result<()> {
+        let label = label.filter(|label| !label.is_empty()).map(str::to_string);
+        self.edit_address(address, |stored| stored.label = label.clone())
+    }
+
+    /// Edits the note, tags and archived state of a stored address, in every account holding it.
+    pub fn annotate_address(
+        &mut self,
+        address: &str,
+        mut edit: impl FnMut(&mut Annotations),
+    ) -> Result<()> {
+        self.edit_address(address, |stored| edit(&mut stored.annotations))
+    }
+
+    /// Exports account names and address labels as BIP329 JSON Lines.
+    ///
+    /// Every account is exported as an `xpub` record, addresses only if they have a label.
+    /// Notes, tags and archived state have no BIP329 field and are not exported.
+    pub fn export_labels(&self) -> Result<String> {
+        let mut accounts: Vec<(CoinType, Purpose, &Bip44Account)> = self
+            .coins
+            .iter()
+            .flat_map(|(coin, purposes)| {
+                purposes.iter().flat_map(move |(purpose, accounts)| {
+                    accounts
+                        .accounts
+                        .values()
+                        .map(move |acct| (*coin, *purpose, acct))
+                })
+            })
+            .collect();
+        accounts.sort_by_key(|(coin, purpose, acct)| (*coin, *purpose as u32, acct.index));
+
+        let mut records = Vec::new();
+        // accounts on several test networks can share an address, it is exported once
+        let mut exported = HashSet::new();
+        for (coin, purpose, acct) in accounts {
+            // accounts without an xpub are only exported through their labelled addresses
+            if coin.adapter().curve() == Curve::Secp256k1 {
+                let export = self.export_account_xpub(coin, purpose, acct.index)?;
+                records.push(LabelRecord {
+                    label_type: LabelType::Xpub,
+                    reference: export.extended_key.clone(),
+                    label: Some(acct.name.clone()),
+                    origin: descriptor_origin(coin, purpose, &export),
+                    spendable: None,
+                });
+            }
+            for change in [Bip44ChangeVal::RECEIVING, Bip44ChangeVal::INTERNAL] {
+                let Some(chain) = acct.changes.get(&change) else {
+                    continue;
+                };
+                let mut addresses: Vec<(&u32, &Bip44Address)> = chain.addresses.iter().collect();
+                addresses.sort_by_key(|(index, _)| **index);
+                for (_, address) in addresses {
+                    let Some(label) = &address.label else {
+                        continue;
+                    };
+                    if exported.insert(address.address_checksummed.as_str()) {
+                        records.push(LabelRecord {
+                            label_type: LabelType::Addr,
+                            reference: address.address_checksummed.clone(),
+                            label: Some(label.clone()),
+                            origin: None,
+                            spendable: None,
+                        });
+                    }
+                }
+            }
+        }
+        write_labels(&records)
+    }
+
+    /// Applies the `addr` and `xpub` records of a BIP329 export to this wallet's addresses and
+    /// accounts. Records for other wallets and the transaction level types are skipped.
+    ///
+    /// Returns how many records were applied.
+    pub fn import_labels(&mut self, jsonl: &str) -> Result<usize> {
+        let records = parse_labels(jsonl)?;
+        let mut accounts = HashMap::new();
+        if records.iter().any(|r| r.label_type == LabelType::Xpub) {
+            for (coin, purposes) in &self.coins {
+                // ed25519 accounts have no xpub to match
+                if coin.adapter().curve() != Curve::Secp256k1 {
+                    continue;
+                }
+                for (purpose, coin_accts) in purposes {
+                    for acct in coin_accts.accounts.values() {
+                        let export = self.export_account_xpub(*coin, *purpose, acct.index)?;
+                        if let Some(key) = normalized_xpub(&export.extended_key) {
+                            // test networks share their account keys
+                            accounts
+                                .entry(key)
+                                .or_insert_with(Vec::new)
+                                .push((*coin, *purpose, acct.index));
                         }
                     }
+                }
+            }
+        }
 
-                    return Ok(());
+        let mut applied = 0;
+        for record in records {
+            let label = match record.label.as_deref() {
+                Some(label) => label,
+                None => continue,
+            };
+            match record.label_type {
+                LabelType::Addr if self.find_address(&record.reference).is_some() => {
+                    self.label_address(&record.reference, Some(label))?;
+                    applied += 1;
+                }
+                LabelType::Xpub => {
+                    let matching = normalized_xpub(&record.reference)
+                        .and_then(|key| accounts.get(&key))
+                        .cloned()
+                        .unwrap_or_default();
+                    for (coin, purpose, index) in matching {
+                        self.rename_account(coin, purpose, index, label)?;
+                        applied += 1;
+                    }
                 }
+                _ => {}
             }
         }
+        Ok(applied)
+    }
+
+    fn account(&self, coin: CoinType, purpose: Purpose, account: u32) -> Result<&Bip44Account> {
+        self.coins
+            .get(&coin)
+            .and_then(|purposes| purposes.get(&purpose))
+            .and_then(|accounts| accounts.accounts.get(&account))
+            .ok_or(Error::msg(UNKNOWN_ACCOUNT))
+    }
 
-        Err(Error::msg(""))
+    fn account_mut(
+        &mut self,
+        coin: CoinType,
+        purpose: Purpose,
+        account: u32,
+    ) -> Result<&mut Bip44Account> {
+        self.coins
+            .get_mut(&coin)
+            .and_then(|purposes| purposes.get_mut(&purpose))
+            .and_then(|accounts| accounts.accounts.get_mut(&account))
+            .ok_or(Error::msg(UNKNOWN_ACCOUNT))
+    }
+
+    fn edit_address(
+        &mut self,
+        address: &str,
+        mut edit: impl FnMut(&mut Bip44Address),
+    ) -> Result<()> {
+        let locations = self.find_address_locations(address).to_vec();
+        if locations.is_empty() {
+            return Err(Error::msg(UNKNOWN_ADDRESS));
+        }
+        for location in locations {
+            let stored = self
+                .account_mut(location.coin, location.purpose, location.account)?
+                .changes
+                .get_mut(&location.change)
+                .and_then(|change| change.addresses.get_mut(&location.index))
+                .ok_or(Error::msg(UNKNOWN_ADDRESS))?;
+            edit(stored);
+        }
+        Ok(())
+    }
+
+    /// Seed of the active tree, fails for watch-only wallets
+    fn seed(&self) -> Result<&Seed> {
+        if self.watch_only {
+            return Err(Error::msg(WATCH_ONLY));
+        }
+        self.session.seed.as_ref().ok_or(Error::msg(NOT_UNLOCKED))
+    }
+
+    /// Derives the P2PKH address hashing the uncompressed key at a BIP44 bitcoin path.
+    ///
+    /// Earlier versions of this wallet handed out these addresses instead of the standard
+    /// compressed ones. This is only meant for finding funds sent to them, the address is not
+    /// stored in the wallet.
+    pub fn recover_uncompressed_address(
+        &self,
+        coin: CoinType,
+        account: u32,
+        change: &Bip44ChangeVal,
+        index: u32,
+    ) -> Result<Bip44Address> {
+        let path = address_path(Purpose::Bip44, coin, account, change, index)?;
+        derive_address(self.seed()?, coin, &path, PubKeyForm::Uncompressed)
+    }
+
+    /// Restores the full lowercase form of EVM addresses that earlier versions stored
+    /// abbreviated (`0x9858…da94`), in the unlocked and the plain account tree. Returns how many
+    /// were fixed.
+    fn repair_evm_addresses(&mut self) -> usize {
+        let mut repaired = 0;
+        let trees = std::iter::once(&mut self.coins)
+            .chain(self.plain.as_mut().map(|plain| &mut plain.coins));
+        for coins in trees {
+            let Some(purposes) = coins.get_mut(&CoinType::Ethereum) else {
+                continue;
+            };
+            let addresses = purposes
+                .values_mut()
+                .flat_map(|accounts| accounts.accounts.values_mut())
+                .flat_map(|acct| acct.changes.values_mut())
+                .flat_map(|change| change.addresses.values_mut());
+            for address in addresses {
+                let full = address.address_checksummed.to_lowercase();
+                if address.address != full {
+                    address.address = full;
+                    repaired += 1;
+                }
+            }
+        }
+        repaired
+    }
+
+    /// Re-derives addresses that were stored under the old `m/44'/coin/change/account/index`
+    /// layout at their correct BIP44 path.
+    ///
+    /// The old path and address are kept in `Bip44Address::legacy` so that anything already sent
+    /// to them can still be found. Only the active tree is migrated, hidden wallets are migrated
+    /// the next time they are unlocked. Returns how many addresses were migrated.
+    fn migrate_legacy_paths(&mut self) -> Result<usize> {
+        let mut migrated = 0;
+        let purposes = self
+            .coins
+            .iter_mut()
+            .flat_map(|(coin, purposes)| purposes.iter_mut().map(move |p| (*coin, p)));
+        for (coin, (purpose, coin_accts)) in purposes {
+            coin_accts.path = format!("m/{}'/{}'", purpose, coin);
+            for acct in coin_accts.accounts.values_mut() {
+                for change in acct.changes.values_mut() {
+                    for (index, address) in change.addresses.iter_mut() {
+                        let path =
+                            address_path(*purpose, coin, acct.index, &change.change, *index)?;
+                        if address.path == path {
+                            continue;
+                        }
+                        let key = self.session.address_key(
+                            coin,
+                            *purpose,
+                            acct.index,
+                            change.change,
+                            *index,
+                            acct.xpub.as_deref(),
+                        )?;
+                        let mut rederived =
+                            address_from_pub_key(coin, &path, &key, PubKeyForm::Compressed)?;
+                        rederived.legacy = Some(LegacyAddress {
+                            path: address.path.to_string(),
+                            address: address.address_checksummed.clone(),
+                        });
+                        rederived.label = address.label.take();
+                        rederived.annotations = std::mem::take(&mut address.annotations);
+                        *address = rederived;
+                        migrated += 1;
+                    }
+                }
+            }
+        }
+        Ok(migrated)
     }
 }
 
-fn mnemonic_as_seed(mnemonic: [u8; 32], maybe_passphrase: Option<&str>) -> Seed {
-    let mnemonic: Mnemonic = Mnemonic::from_entropy(mnemonic, Language::English);
-    mnemonic.to_seed(maybe_passphrase.unwrap_or(""))
+/// Builds the path of an address, `m/purpose'/coin'/account'/change/index` for every coin
+/// except the ones whose adapter uses another layout.
+pub fn address_path(
+    purpose: Purpose,
+    coin: CoinType,
+    account: u32,
+    change: &Bip44ChangeVal,
+    index: u32,
+) -> Result<DerivationPath> {
+    coin.adapter()
+        .address_path(purpose, account, *change, index)
+}
+
+fn derive_address(
+    seed: &Seed,
+    coin: CoinType,
+    path: &DerivationPath,
+    key_form: PubKeyForm,
+) -> Result<Bip44Address> {
+    let key_pair = derive_child(seed, path)?;
+    let key_bytes: [u8; 33] = key_pair.pub_key.public_key().to_bytes();
+    address_from_pub_key(coin, path, &key_bytes, key_form)
+}
+
+fn address_from_pub_key(
+    coin: CoinType,
+    path: &DerivationPath,
+    key_bytes: &[u8],
+    key_form: PubKeyForm,
+) -> Result<Bip44Address> {
+    let encoded = coin
+        .adapter()
+        .encode_address(key_bytes, path.purpose(), key_form)?;
+    Ok(Bip44Address {
+        path: path.clone(),
+        pub_key: key_bytes.to_vec(),
+        address: encoded.address,
+        address_checksummed: encoded.checksummed,
+        key_form: encoded.key_form,
+        x_only_pub_key: taproot_internal_key(path, key_bytes)?,
+        legacy: None,
+        label: None,
+        annotations: Annotations::default(),
+    })
+}
+
+fn taproot_internal_key(path: &DerivationPath, key_bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+    match path.purpose() {
+        Some(Purpose::Bip86) => Ok(Some(x_only_pub_key(key_bytes.try_into()?)?.to_vec())),
+        _ => Ok(None),
+    }
 }
 
+// the first versions of the wallet wrote the wallet itself, without any encryption
+fn is_unencrypted_wallet(contents: &serde_json::Value) -> bool {
+    contents.get("mnemonic").is_some() && contents.get("ciphertext").is_none()
+}
+
+fn write_synced(path: &Path, encrypted: &EncryptedFile) -> Result<()> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    let mut buf_writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut buf_writer, encrypted)?;
+    buf_writer.flush()?;
+    buf_writer.get_ref().sync_all()?;
+    Ok(())
+}
+
+// makes the rename itself durable, directories can't be opened for syncing on windows
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::File::open(parent)?.sync_all()?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn mnemonic_as_seed(mnemonic: &[u8], maybe_passphrase: Option<&str>) -> Result<Seed> {
+    let mnemonic: Mnemonic = Mnemonic::from_entropy_in(Language::English, mnemonic)?;
+    Ok(Seed::new(mnemonic.to_seed(maybe_passphrase.unwrap_or(""))))
+}
+
+/// Selects the chain `Wallet::new_address` and `Wallet::new_addresses` add to.
+///
+/// Defaults to the BIP44 receiving chain of the account, change both with the builder methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct NewAddressParams {
     coin: CoinType,
+    purpose: Purpose,
     // TODO check if u32 is the appropriate size for this
     account: u32,
     change: Bip44ChangeVal,
 }
 
-pub fn gen_mnemonic() -> Mnemonic {
-    let mnemonic: Mnemonic = Mnemonic::random(&mut OsRng, Default::default());
-    mnemonic
+impl NewAddressParams {
+    pub fn new(coin: CoinType, account: u32) -> Self {
+        NewAddressParams {
+            coin,
+            purpose: Purpose::Bip44,
+            account,
+            change: Bip44ChangeVal::RECEIVING,
+        }
+    }
+
+    pub fn purpose(mut self, purpose: Purpose) -> Self {
+        self.purpose = purpose;
+        self
+    }
+
+    pub fn change(mut self, change: Bip44ChangeVal) -> Self {
+        self.change = change;
+        self
+    }
+}
+
+/// Hex encoded fingerprint of the master public key, as shown by hardware wallets.
+pub fn master_fingerprint(seed: &Seed) -> Result<String> {
+    let master = XPrv::new(seed.as_bytes())?;
+    Ok(hex::encode(master.public_key().fingerprint()))
+}
+
+/// Validates an English BIP39 phrase (word list membership and checksum) and returns its entropy.
+///
+/// Case and surrounding whitespace are ignored.
+pub fn parse_mnemonic(phrase: &str) -> Result<Vec<u8>> {
+    let normalised = phrase
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>()
+        .join(" ");
+    let mnemonic =
+        Mnemonic::parse_in_normalized(Language::English, &normalised).map_err(|err| match err {
+            bip39::Error::UnknownWord(index) => Error::msg(format!(
+                "\"{}\" (word {}) is not in the BIP39 word list",
+                normalised.split(' ').nth(index).unwrap_or_default(),
+                index + 1
+            )),
+            err => Error::msg(err.to_string()),
+        })?;
+    Ok(mnemonic.to_entropy())
+}
+
+/// Generates a random English mnemonic with 12, 15, 18, 21 or 24 words.
+pub fn gen_mnemonic(word_count: usize) -> Result<Mnemonic> {
+    let mut entropy = vec![0u8; entropy_len_for_word_count(word_count)?];
+    OsRng.fill_bytes(&mut entropy);
+    let mnemonic: Mnemonic = Mnemonic::from_entropy_in(Language::English, &entropy)?;
+    Ok(mnemonic)
+}
+
+// every 3 words encode 32 bits of entropy (plus one checksum bit per word)
+fn entropy_len_for_word_count(word_count: usize) -> Result<usize> {
+    match word_count {
+        12 | 15 | 18 | 21 | 24 => Ok(word_count / 3 * 4),
+        _ => Err(Error::msg(INVALID_WORD_COUNT)),
+    }
+}
+
+fn word_count_for_entropy(entropy_len: usize) -> Result<usize> {
+    match entropy_len {
+        16 | 20 | 24 | 28 | 32 => Ok(entropy_len / 4 * 3),
+        _ => Err(Error::msg(INVALID_WORD_COUNT)),
+    }
+}
+
+fn default_word_count() -> usize {
+    DEFAULT_WORD_COUNT
 }
 
 pub struct DerivedKeyPair {
@@ -304,37 +1499,32 @@ pub struct DerivedKeyPair {
     pub pub_key: ExtendedPublicKey<VerifyingKey>,
 }
 
-pub fn derive_child(seed: &Seed, path: &str) -> Result<DerivedKeyPair, Error> {
-    validate_bip_44_path(path)?;
-    let priv_key = XPrv::derive_from_path(&seed, &path.parse()?)?;
+/// Derives the key pair at any BIP32 `path` from the seed.
+///
+/// Paths under the BIP44/49/84/86 purposes must follow their hardening rules, anything else
+/// (BIP85, SLIP-10 style paths...) is derived as is.
+pub fn derive_child(seed: &Seed, path: &DerivationPath) -> Result<DerivedKeyPair, Error> {
+    path.validate_purpose()?;
+    let priv_key = XPrv::derive_from_path(seed, &path.to_bip32()?)?;
     let pub_key = priv_key.public_key();
     Ok(DerivedKeyPair { priv_key, pub_key })
 }
 
-// private utility functions
-
-fn validate_bip_44_path(path: &str) -> Result<(), Error> {
-    // Split the input string by '/'
-    let parts: Vec<&str> = path.split('/').collect();
-    // Check that the string starts with "m"
-    if parts.get(0) != Some(&"m") {
-        return Err(Error::msg(INVALID_BIP44_PATH_FORMT));
-    }
-    // Check that there are exactly 5 parts in the path
-    if parts.len() != 5 {
-        return Err(Error::msg(INVALID_BIP44_PATH_FORMT));
-    }
-    // Check that each part is a valid number
-    for i in 1..=4 {
-        if !parts[i].parse::<i64>().is_ok() {
-            return Err(Error::msg(INVALID_BIP44_PATH_FORMT));
-        }
+/// Checks that `xpub` is a public key at account depth and returns its account index.
+fn parse_account_xpub(xpub: &str) -> Result<u32> {
+    let xpub = parse_xpub(xpub)?;
+    let child_number = xpub.attrs().child_number;
+    if xpub.attrs().depth != 3 || !child_number.is_hardened() {
+        return Err(Error::msg(NOT_AN_ACCOUNT_XPUB));
     }
-    // Check that the third part ends with "'"
-    if !parts[2].ends_with("'") {
-        return Err(Error::msg(INVALID_WALLET_PATH));
-    }
-    Ok(())
+    Ok(child_number.index())
+}
+
+/// The key of an extended public key in any SLIP-132 version, so that e.g. the `xpub` and `zpub`
+/// of an account compare equal.
+fn normalized_xpub(extended_key: &str) -> Option<String> {
+    let xpub = parse_xpub(extended_key).ok()?;
+    Some(xpub.to_string(Prefix::XPUB))
 }
 
 /// util for reused logic in Wallet::new_account function
@@ -368,6 +1558,8 @@ fn new_account(accounts: &mut Accounts, account_name: &str) -> Result<u32> {
             index,
             name: String::from(account_name),
             changes,
+            xpub: None,
+            annotations: Annotations::default(),
         },
     );
 
@@ -0,0 +1,100 @@
+use super::coins::{CoinAdapter, Curve, EncodedAddress};
+use super::core::{CoinType, PubKeyForm};
+use super::path::Purpose;
+use anyhow::{Error, Result};
+use bitcoin::bech32::{self, FromBase32, ToBase32, Variant};
+use bitcoin::hashes::{hash160, Hash};
+use secp256k1::PublicKey;
+
+// ERR MESSAGES
+const INVALID_COMPRESSED_KEY: &str = "expected a 33 byte compressed public key";
+const INVALID_ADDRESS: &str = "expected a bech32 address of 20 or 32 bytes";
+const WRONG_HRP: &str = "address belongs to a different chain";
+
+/// `CoinAdapter` of a Cosmos SDK chain, addresses are the bech32 encoded
+/// RIPEMD160(SHA256(compressed key)) behind the chain's human readable part.
+///
+/// Chains sharing coin type 118 derive the same keys, only the HRP differs. The coin type and HRP
+/// are per adapter, but chains can't be registered at runtime: a new one, whatever its coin type,
+/// needs a `CoinType` variant, an entry in `coins::COINS` and its adapter in `coins::adapter`.
+pub struct CosmosAdapter {
+    coin: CoinType,
+    slip44: u32,
+    hrp: &'static str,
+}
+
+impl CosmosAdapter {
+    pub const fn new(coin: CoinType, slip44: u32, hrp: &'static str) -> Self {
+        CosmosAdapter { coin, slip44, hrp }
+    }
+
+    pub fn hrp(&self) -> &'static str {
+        self.hrp
+    }
+}
+
+impl CoinAdapter for CosmosAdapter {
+    fn coin(&self) -> CoinType {
+        self.coin
+    }
+
+    fn slip44(&self) -> u32 {
+        self.slip44
+    }
+
+    fn curve(&self) -> Curve {
+        Curve::Secp256k1
+    }
+
+    fn purposes(&self) -> &'static [Purpose] {
+        &[Purpose::Bip44]
+    }
+
+    fn encode_address(
+        &self,
+        pub_key: &[u8],
+        _purpose: Option<Purpose>,
+        _key_form: PubKeyForm,
+    ) -> Result<EncodedAddress> {
+        if pub_key.len() != 33 {
+            return Err(Error::msg(INVALID_COMPRESSED_KEY));
+        }
+        let pub_key = PublicKey::from_slice(pub_key)?;
+        let hash = hash160::Hash::hash(&pub_key.serialize());
+        let address = encode(self.hrp, hash.as_byte_array())?;
+        Ok(EncodedAddress {
+            checksummed: address.clone(),
+            address,
+            key_form: PubKeyForm::Compressed,
+        })
+    }
+
+    fn checksum_address(&self, address: &str) -> Result<String> {
+        let (hrp, bytes) = decode(address)?;
+        if hrp != self.hrp {
+            return Err(Error::msg(WRONG_HRP));
+        }
+        encode(self.hrp, &bytes)
+    }
+}
+
+/// The same account on another Cosmos chain sharing its coin type, e.g. the `osmo1...` form of a
+/// `cosmos1...` address.
+pub fn address_for_hrp(address: &str, hrp: &str) -> Result<String> {
+    let (_, bytes) = decode(address)?;
+    encode(&hrp.to_lowercase(), &bytes)
+}
+
+fn encode(hrp: &str, bytes: &[u8]) -> Result<String> {
+    Ok(bech32::encode(hrp, bytes.to_base32(), Variant::Bech32)?)
+}
+
+// account addresses are 20 bytes, module and interchain accounts 32
+fn decode(address: &str) -> Result<(String, Vec<u8>)> {
+    let (hrp, data, variant) = bech32::decode(address.trim())?;
+    let bytes = Vec::<u8>::from_base32(&data)?;
+    if variant != Variant::Bech32 || !matches!(bytes.len(), 20 | 32) {
+        return Err(Error::msg(INVALID_ADDRESS));
+    }
+    Ok((hrp, bytes))
+}
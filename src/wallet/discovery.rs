@@ -0,0 +1,67 @@
+use super::core::{Bip44Address, CoinType};
+use anyhow::Result;
+use std::collections::HashSet;
+
+/// Number of consecutive unused addresses after which a chain is assumed to be exhausted, as
+/// recommended by BIP44
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// Tells account discovery whether an address has ever been used, e.g. by asking an Electrum
+/// server or a block explorer.
+pub trait AddressHistory {
+    /// `address` is in its checksummed form for coins that have one
+    fn is_used(&self, coin: CoinType, address: &str) -> Result<bool>;
+}
+
+/// In-memory `AddressHistory`, for tests and for checking discovery offline.
+#[derive(Debug, Default, Clone)]
+pub struct MockAddressHistory {
+    used: HashSet<(CoinType, String)>,
+}
+
+impl MockAddressHistory {
+    pub fn new() -> Self {
+        MockAddressHistory::default()
+    }
+
+    pub fn mark_used(&mut self, coin: CoinType, address: &str) {
+        self.used.insert((coin, address.to_string()));
+    }
+}
+
+impl AddressHistory for MockAddressHistory {
+    fn is_used(&self, coin: CoinType, address: &str) -> Result<bool> {
+        Ok(self.used.contains(&(coin, address.to_string())))
+    }
+}
+
+/// Derives addresses `0, 1, 2...` of one chain with `derive` until `gap_limit` unused addresses
+/// in a row are found or the chain is full.
+///
+/// Returns every address up to and including the last used one, so nothing if the chain was
+/// never used.
+pub fn scan_chain(
+    history: &dyn AddressHistory,
+    coin: CoinType,
+    gap_limit: u32,
+    mut derive: impl FnMut(u32) -> Result<Bip44Address>,
+) -> Result<Vec<Bip44Address>> {
+    let mut derived = Vec::new();
+    let mut used_len = 0;
+    let mut unused_in_a_row = 0;
+    let mut index = 0;
+    let chain_len = coin.adapter().addresses_per_chain();
+    while unused_in_a_row < gap_limit && index < chain_len {
+        let address = derive(index)?;
+        if history.is_used(coin, &address.address_checksummed)? {
+            used_len = derived.len() + 1;
+            unused_in_a_row = 0;
+        } else {
+            unused_in_a_row += 1;
+        }
+        derived.push(address);
+        index += 1;
+    }
+    derived.truncate(used_len);
+    Ok(derived)
+}
@@ -0,0 +1,295 @@
+use anyhow::{Error, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+
+// ERR MESSAGES
+pub const INCORRECT_PASSWORD: &str = "incorrect wallet password";
+pub const TAMPERED_WALLET_FILE: &str =
+    "wallet file failed its integrity check and may have been tampered with";
+const UNSUPPORTED_FILE_VERSION: &str = "unsupported wallet file version";
+const UNSUPPORTED_ALGORITHM: &str = "unsupported wallet file encryption algorithm";
+const INVALID_HEX_FIELD: &str = "malformed encrypted wallet field";
+
+const FILE_VERSION: u32 = 1;
+const KDF_ALGORITHM: &str = "argon2id";
+const CIPHER_ALGORITHM: &str = "xchacha20poly1305";
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+// authenticated with an empty message so a wrong password can be told apart from a damaged payload
+const PASSWORD_CHECK_AAD: &[u8] = b"wolet password check";
+// HMAC key turning a BIP39 seed into the key of its hidden wallet
+const TREE_KEY_DOMAIN: &[u8] = b"wolet hidden wallet";
+const SEALED_TREE_AAD: &[u8] = b"wolet hidden wallet tree";
+
+/// Argon2id settings stored alongside the ciphertext so they can be raised later without
+/// breaking older wallet files.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub salt: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PasswordCheck {
+    pub nonce: String,
+    pub tag: String,
+}
+
+/// On-disk representation of an encrypted wallet. Everything except `ciphertext` is public
+/// metadata, but all of it is authenticated.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedFile {
+    pub version: u32,
+    pub kdf: KdfParams,
+    pub cipher: String,
+    pub password_check: PasswordCheck,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Symmetric key derived from the wallet password, kept in memory while the wallet is unlocked
+/// so that saving does not have to re-run the KDF.
+#[derive(Clone)]
+pub struct FileKey {
+    key: [u8; KEY_LEN],
+    kdf: KdfParams,
+}
+
+impl fmt::Debug for FileKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FileKey")
+            .field("key", &"<redacted>")
+            .field("kdf", &self.kdf)
+            .finish()
+    }
+}
+
+impl FileKey {
+    /// Derives a key for a brand new wallet file using a fresh random salt.
+    pub fn new(password: &str) -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let kdf = KdfParams {
+            algorithm: KDF_ALGORITHM.to_string(),
+            salt: hex::encode(salt),
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        };
+        Self::derive(password, kdf)
+    }
+
+    fn derive(password: &str, kdf: KdfParams) -> Result<Self> {
+        if kdf.algorithm != KDF_ALGORITHM {
+            return Err(Error::msg(UNSUPPORTED_ALGORITHM));
+        }
+        let salt = decode_hex(&kdf.salt)?;
+        let params = Params::new(
+            kdf.memory_kib,
+            kdf.iterations,
+            kdf.parallelism,
+            Some(KEY_LEN),
+        )
+        .map_err(|err| Error::msg(err.to_string()))?;
+
+        let mut key = [0u8; KEY_LEN];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .map_err(|err| Error::msg(err.to_string()))?;
+        Ok(FileKey { key, kdf })
+    }
+
+    /// Encrypts `plaintext` under a fresh nonce. The key and salt are reused between saves.
+    pub fn seal(&self, plaintext: &[u8]) -> Result<EncryptedFile> {
+        let cipher = XChaCha20Poly1305::new(&self.key.into());
+
+        let check_nonce = random_nonce();
+        let check_tag = cipher
+            .encrypt(
+                XNonce::from_slice(&check_nonce),
+                Payload {
+                    msg: &[],
+                    aad: PASSWORD_CHECK_AAD,
+                },
+            )
+            .map_err(|_| Error::msg(TAMPERED_WALLET_FILE))?;
+
+        let nonce = random_nonce();
+        let mut file = EncryptedFile {
+            version: FILE_VERSION,
+            kdf: self.kdf.clone(),
+            cipher: CIPHER_ALGORITHM.to_string(),
+            password_check: PasswordCheck {
+                nonce: hex::encode(check_nonce),
+                tag: hex::encode(check_tag),
+            },
+            nonce: hex::encode(nonce),
+            ciphertext: String::new(),
+        };
+        let aad = file.associated_data();
+        let ciphertext = cipher
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| Error::msg(TAMPERED_WALLET_FILE))?;
+        file.ciphertext = hex::encode(ciphertext);
+        Ok(file)
+    }
+}
+
+/// Account tree of a hidden wallet inside the wallet file, encrypted under a `TreeKey` so that
+/// the wallet password alone doesn't reveal it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SealedTree {
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Key of a hidden wallet, derived from the seed of its BIP39 passphrase. Anyone who can open
+/// the tree can already derive all of its keys.
+#[derive(Clone)]
+pub struct TreeKey {
+    key: [u8; KEY_LEN],
+}
+
+impl fmt::Debug for TreeKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TreeKey")
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl TreeKey {
+    pub fn from_seed(seed: &[u8]) -> Result<Self> {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(TREE_KEY_DOMAIN)?;
+        mac.update(seed);
+        Ok(TreeKey {
+            key: mac.finalize().into_bytes().into(),
+        })
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> Result<SealedTree> {
+        let nonce = random_nonce();
+        let ciphertext = XChaCha20Poly1305::new(&self.key.into())
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: SEALED_TREE_AAD,
+                },
+            )
+            .map_err(|_| Error::msg(TAMPERED_WALLET_FILE))?;
+        Ok(SealedTree {
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// `None` if `tree` belongs to another passphrase.
+    pub fn open(&self, tree: &SealedTree) -> Option<Vec<u8>> {
+        let nonce = decode_nonce(&tree.nonce).ok()?;
+        let ciphertext = decode_hex(&tree.ciphertext).ok()?;
+        XChaCha20Poly1305::new(&self.key.into())
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: SEALED_TREE_AAD,
+                },
+            )
+            .ok()
+    }
+}
+
+impl EncryptedFile {
+    /// Derives the key from `password` and decrypts the payload.
+    ///
+    /// Returns `INCORRECT_PASSWORD` when the password check fails and `TAMPERED_WALLET_FILE`
+    /// when the password is right but the payload or its metadata no longer authenticates.
+    pub fn open(&self, password: &str) -> Result<(Vec<u8>, FileKey)> {
+        if self.version != FILE_VERSION {
+            return Err(Error::msg(UNSUPPORTED_FILE_VERSION));
+        }
+        if self.cipher != CIPHER_ALGORITHM {
+            return Err(Error::msg(UNSUPPORTED_ALGORITHM));
+        }
+        let file_key = FileKey::derive(password, self.kdf.clone())?;
+        let cipher = XChaCha20Poly1305::new(&file_key.key.into());
+
+        let check_nonce = decode_nonce(&self.password_check.nonce)?;
+        let check_tag = decode_hex(&self.password_check.tag)?;
+        cipher
+            .decrypt(
+                XNonce::from_slice(&check_nonce),
+                Payload {
+                    msg: &check_tag,
+                    aad: PASSWORD_CHECK_AAD,
+                },
+            )
+            .map_err(|_| Error::msg(INCORRECT_PASSWORD))?;
+
+        let nonce = decode_nonce(&self.nonce)?;
+        let ciphertext = decode_hex(&self.ciphertext)?;
+        let aad = self.associated_data();
+        let plaintext = cipher
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| Error::msg(TAMPERED_WALLET_FILE))?;
+        Ok((plaintext, file_key))
+    }
+
+    /// Header fields bound to the ciphertext so they cannot be swapped out independently.
+    fn associated_data(&self) -> String {
+        format!(
+            "wolet/v{}/{}/{}/{}/{}/{}/{}/{}",
+            self.version,
+            self.kdf.algorithm,
+            self.kdf.salt,
+            self.kdf.memory_kib,
+            self.kdf.iterations,
+            self.kdf.parallelism,
+            self.cipher,
+            self.password_check.nonce,
+        )
+    }
+}
+
+fn random_nonce() -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+fn decode_hex(field: &str) -> Result<Vec<u8>> {
+    hex::decode(field).map_err(|_| Error::msg(INVALID_HEX_FIELD))
+}
+
+fn decode_nonce(field: &str) -> Result<Vec<u8>> {
+    let nonce = decode_hex(field)?;
+    if nonce.len() != NONCE_LEN {
+        return Err(Error::msg(TAMPERED_WALLET_FILE));
+    }
+    Ok(nonce)
+}
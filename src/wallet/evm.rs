@@ -1,40 +1,82 @@
+use super::coins::{CoinAdapter, Curve, EncodedAddress};
+use super::core::{CoinType, PubKeyForm};
+use super::path::Purpose;
 use anyhow::{Error, Result};
 use hex::encode;
-use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use secp256k1::PublicKey;
 use tiny_keccak::keccak256;
-use web3::{
-    transports::{self, WebSocket},
-    types::{Address, TransactionParameters, H256},
-    Web3,
-};
+use web3::types::Address;
 
-pub async fn establish_web3_connection(url: &str) -> Result<Web3<WebSocket>> {
-    let transport = web3::transports::WebSocket::new(url).await?;
-    Ok(web3::Web3::new(transport))
-}
+const INVALID_ADDRESS: &str = "expected a 0x prefixed address of 40 hex digits";
+const INVALID_ADDRESS_CHECKSUM: &str = "mixed case address does not match its EIP-55 checksum";
+
+/// `CoinAdapter` of Ethereum, addresses are the last 20 bytes of the keccak hash of the
+/// uncompressed key.
+pub struct EthereumAdapter;
+
+impl CoinAdapter for EthereumAdapter {
+    fn coin(&self) -> CoinType {
+        CoinType::Ethereum
+    }
+
+    fn slip44(&self) -> u32 {
+        60
+    }
+
+    fn curve(&self) -> Curve {
+        Curve::Secp256k1
+    }
 
-pub async fn sign_and_send(
-    web3: &Web3<transports::WebSocket>,
-    transaction: TransactionParameters,
-    secret_key: &SecretKey,
-) -> Result<H256> {
-    let signed = web3
-        .accounts()
-        .sign_transaction(transaction, secret_key)
-        .await?;
-    let transaction_result = web3
-        .eth()
-        .send_raw_transaction(signed.raw_transaction)
-        .await?;
-    Ok(transaction_result)
+    fn purposes(&self) -> &'static [Purpose] {
+        &[Purpose::Bip44]
+    }
+
+    fn encode_address(
+        &self,
+        pub_key: &[u8],
+        _purpose: Option<Purpose>,
+        _key_form: PubKeyForm,
+    ) -> Result<EncodedAddress> {
+        let pub_key = PublicKey::from_slice(pub_key)?.serialize_uncompressed();
+        let address = address_from_pubkey(pub_key);
+        Ok(EncodedAddress {
+            // `Display` of H160 abbreviates the address, `Debug` prints all of it
+            address: format!("{:?}", address),
+            checksummed: to_checksum_address(&address),
+            // keccak of the uncompressed key
+            key_form: PubKeyForm::Uncompressed,
+        })
+    }
+
+    /// Any case is accepted, but a mixed case address must carry a valid EIP-55 checksum.
+    fn checksum_address(&self, address: &str) -> Result<String> {
+        let address = address.trim();
+        let digits = address
+            .strip_prefix("0x")
+            .or_else(|| address.strip_prefix("0X"))
+            .filter(|digits| digits.len() == 40)
+            .ok_or(Error::msg(INVALID_ADDRESS))?;
+        let bytes = hex::decode(digits).map_err(|_| Error::msg(INVALID_ADDRESS))?;
+        let checksummed = to_checksum_address(&Address::from_slice(&bytes));
+        let mixed_case = digits.chars().any(|c| c.is_ascii_lowercase())
+            && digits.chars().any(|c| c.is_ascii_uppercase());
+        if mixed_case && digits != &checksummed[2..] {
+            return Err(Error::msg(INVALID_ADDRESS_CHECKSUM));
+        }
+        Ok(checksummed)
+    }
+
+    fn case_insensitive(&self, _address: &str) -> bool {
+        true
+    }
 }
 
 pub fn to_checksum_address(address: &Address) -> String {
-    let addr = address.clone();
+    let addr = *address;
 
     let address_lower: String = format!("{:?}", addr);
     let chars: Vec<char> = address_lower.chars().collect();
-    let address_lower_hex: String = chars[2..].into_iter().collect();
+    let address_lower_hex: String = chars[2..].iter().collect();
     let addr_hash = encode(keccak256(address_lower_hex.as_bytes()));
 
     format!(
@@ -52,13 +94,6 @@ pub fn to_checksum_address(address: &Address) -> String {
     )
 }
 
-pub fn uncompress_pub_key(compressed_pubkey: [u8; 33]) -> [u8; 65] {
-    let secp = Secp256k1::new();
-    let compressed_pub_key =
-        PublicKey::from_slice(&compressed_pubkey).expect("Invalid compressed public key");
-    compressed_pub_key.serialize_uncompressed()
-}
-
 pub fn address_from_pubkey(pub_key: [u8; 65]) -> Address {
     // let pub_key = pub_key.serialize_uncompressed();
     // use a result for this with proper error handling
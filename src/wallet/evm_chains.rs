@@ -0,0 +1,279 @@
+use anyhow::{Error, Result};
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use web3::{
+    transports::{Either, Http, WebSocket},
+    types::{Address, TransactionParameters, H256, U256},
+    Transport, Web3,
+};
+
+// ERR MESSAGES
+const NO_RPC_ENDPOINT: &str = "chain has no RPC endpoint";
+const WRONG_CHAIN_ID: &str = "RPC endpoint is connected to a different chain";
+const TRANSACTION_CHAIN_MISMATCH: &str = "transaction is for a different chain";
+
+pub const ETHEREUM_MAINNET: u64 = 1;
+pub const SEPOLIA: u64 = 11155111;
+pub const POLYGON: u64 = 137;
+pub const ARBITRUM_ONE: u64 = 42161;
+pub const OPTIMISM: u64 = 10;
+pub const BASE: u64 = 8453;
+
+/// Prefix of the environment variables overriding the RPC endpoint of a chain, e.g.
+/// `EVM_RPC_11155111=wss://sepolia.infura.io/ws/v3/<key>`
+pub const RPC_ENV_PREFIX: &str = "EVM_RPC_";
+
+/// An EVM network. Every chain shares the keys and addresses of the coin type 60 accounts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvmChain {
+    /// EIP-155 chain id, signed into every transaction
+    pub chain_id: u64,
+    pub name: String,
+    pub native_symbol: String,
+    pub decimals: u8,
+    /// Tried in order, `ws://` and `wss://` endpoints connect over a websocket
+    pub rpc_endpoints: Vec<String>,
+    pub explorer_url: Option<String>,
+    pub testnet: bool,
+}
+
+impl EvmChain {
+    fn builtin(
+        chain_id: u64,
+        name: &str,
+        native_symbol: &str,
+        rpc_endpoint: &str,
+        explorer_url: &str,
+        testnet: bool,
+    ) -> Self {
+        EvmChain {
+            chain_id,
+            name: name.to_string(),
+            native_symbol: native_symbol.to_string(),
+            decimals: 18,
+            rpc_endpoints: vec![rpc_endpoint.to_string()],
+            explorer_url: Some(explorer_url.to_string()),
+            testnet,
+        }
+    }
+
+    pub fn explorer_address_url(&self, address: &str) -> Option<String> {
+        self.explorer_url
+            .as_ref()
+            .map(|url| format!("{}/address/{}", url.trim_end_matches('/'), address))
+    }
+
+    pub fn explorer_tx_url(&self, hash: &H256) -> Option<String> {
+        self.explorer_url
+            .as_ref()
+            .map(|url| format!("{}/tx/{:?}", url.trim_end_matches('/'), hash))
+    }
+
+    /// `amount` of the smallest unit in whole coins, e.g. `1.5 ETH`
+    pub fn format_amount(&self, amount: U256) -> String {
+        let unit = U256::exp10(self.decimals as usize);
+        let whole = amount / unit;
+        let fraction = format!(
+            "{:0>width$}",
+            (amount % unit).to_string(),
+            width = self.decimals as usize
+        );
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            format!("{} {}", whole, self.native_symbol)
+        } else {
+            format!("{}.{} {}", whole, fraction, self.native_symbol)
+        }
+    }
+
+    /// Connects to the first endpoint that answers and serves this chain, in the order of
+    /// `rpc_endpoints`. Fails with the error of the last endpoint if none does.
+    pub async fn connect(&self) -> Result<Web3<Either<WebSocket, Http>>> {
+        let mut last_error = Error::msg(NO_RPC_ENDPOINT);
+        for endpoint in &self.rpc_endpoints {
+            match self.connect_to(endpoint).await {
+                Ok(web3) => return Ok(web3),
+                Err(err) => last_error = err,
+            }
+        }
+        Err(last_error)
+    }
+
+    async fn connect_to(&self, endpoint: &str) -> Result<Web3<Either<WebSocket, Http>>> {
+        let transport = if endpoint.starts_with("ws://") || endpoint.starts_with("wss://") {
+            Either::Left(WebSocket::new(endpoint).await?)
+        } else {
+            Either::Right(Http::new(endpoint)?)
+        };
+        let web3 = Web3::new(transport);
+        if web3.eth().chain_id().await? != U256::from(self.chain_id) {
+            return Err(Error::msg(WRONG_CHAIN_ID));
+        }
+        Ok(web3)
+    }
+}
+
+/// Balance of an address on one chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainBalance {
+    pub chain_id: u64,
+    pub address: Address,
+    pub balance: U256,
+}
+
+/// A transaction sent to one chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainTransaction {
+    pub chain_id: u64,
+    pub hash: H256,
+}
+
+pub async fn balance<T: Transport>(
+    web3: &Web3<T>,
+    chain: &EvmChain,
+    address: Address,
+) -> Result<ChainBalance> {
+    let balance = web3.eth().balance(address, None).await?;
+    Ok(ChainBalance {
+        chain_id: chain.chain_id,
+        address,
+        balance,
+    })
+}
+
+/// Signs `transaction` for `chain` and sends it. A transaction that already names another
+/// chain id is refused rather than replayed elsewhere.
+pub async fn sign_and_send<T: Transport>(
+    web3: &Web3<T>,
+    chain: &EvmChain,
+    mut transaction: TransactionParameters,
+    secret_key: &SecretKey,
+) -> Result<ChainTransaction> {
+    match transaction.chain_id {
+        Some(chain_id) if chain_id != chain.chain_id => {
+            return Err(Error::msg(TRANSACTION_CHAIN_MISMATCH))
+        }
+        _ => transaction.chain_id = Some(chain.chain_id),
+    }
+    let signed = web3
+        .accounts()
+        .sign_transaction(transaction, secret_key)
+        .await?;
+    let hash = web3
+        .eth()
+        .send_raw_transaction(signed.raw_transaction)
+        .await?;
+    Ok(ChainTransaction {
+        chain_id: chain.chain_id,
+        hash,
+    })
+}
+
+/// The EVM chains the wallet knows, keyed by chain id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainRegistry {
+    chains: BTreeMap<u64, EvmChain>,
+}
+
+impl Default for ChainRegistry {
+    fn default() -> Self {
+        let mut registry = ChainRegistry {
+            chains: BTreeMap::new(),
+        };
+        for chain in [
+            EvmChain::builtin(
+                ETHEREUM_MAINNET,
+                "Ethereum",
+                "ETH",
+                "https://ethereum-rpc.publicnode.com",
+                "https://etherscan.io",
+                false,
+            ),
+            EvmChain::builtin(
+                SEPOLIA,
+                "Sepolia",
+                "ETH",
+                "https://ethereum-sepolia-rpc.publicnode.com",
+                "https://sepolia.etherscan.io",
+                true,
+            ),
+            EvmChain::builtin(
+                POLYGON,
+                "Polygon",
+                "POL",
+                "https://polygon-rpc.com",
+                "https://polygonscan.com",
+                false,
+            ),
+            EvmChain::builtin(
+                ARBITRUM_ONE,
+                "Arbitrum One",
+                "ETH",
+                "https://arb1.arbitrum.io/rpc",
+                "https://arbiscan.io",
+                false,
+            ),
+            EvmChain::builtin(
+                OPTIMISM,
+                "OP Mainnet",
+                "ETH",
+                "https://mainnet.optimism.io",
+                "https://optimistic.etherscan.io",
+                false,
+            ),
+            EvmChain::builtin(
+                BASE,
+                "Base",
+                "ETH",
+                "https://mainnet.base.org",
+                "https://basescan.org",
+                false,
+            ),
+        ] {
+            registry.insert(chain);
+        }
+        registry
+    }
+}
+
+impl ChainRegistry {
+    /// The built-in chains with endpoints from `EVM_RPC_<chain id>` variables tried first.
+    pub fn from_env() -> Self {
+        let mut registry = ChainRegistry::default();
+        for (key, endpoint) in env::vars() {
+            let Some(chain_id) = key
+                .strip_prefix(RPC_ENV_PREFIX)
+                .and_then(|chain_id| chain_id.parse().ok())
+            else {
+                continue;
+            };
+            if let Some(chain) = registry.chains.get_mut(&chain_id) {
+                chain.rpc_endpoints.insert(0, endpoint);
+            }
+        }
+        registry
+    }
+
+    /// Adds a chain, replacing and returning any chain with the same id.
+    pub fn insert(&mut self, chain: EvmChain) -> Option<EvmChain> {
+        self.chains.insert(chain.chain_id, chain)
+    }
+
+    pub fn get(&self, chain_id: u64) -> Option<&EvmChain> {
+        self.chains.get(&chain_id)
+    }
+
+    /// Case-insensitive lookup by name
+    pub fn by_name(&self, name: &str) -> Option<&EvmChain> {
+        self.chains
+            .values()
+            .find(|chain| chain.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Chains ordered by chain id
+    pub fn chains(&self) -> impl Iterator<Item = &EvmChain> {
+        self.chains.values()
+    }
+}
@@ -0,0 +1,90 @@
+use super::coins::COINS;
+use super::core::CoinType;
+use super::path::{DerivationPath, Purpose};
+use anyhow::{Error, Result};
+use bip32::{ExtendedKey, Prefix, XPub};
+use std::{fmt, str::FromStr};
+
+// ERR MESSAGES
+const NOT_AN_XPUB: &str = "expected an extended public key, not a private key";
+const UNKNOWN_KEY_VERSION: &str = "extended key version is not used by any supported coin";
+
+/// SLIP-132 version of an account's extended public key, e.g. `zpub` for BIP84 bitcoin.
+///
+/// Taproot has no registered version so BIP86 accounts use `xpub`/`tpub` like other wallets.
+pub fn public_prefix(coin: CoinType, purpose: Purpose) -> Prefix {
+    coin.adapter().public_prefix(purpose)
+}
+
+/// SLIP-132 version of an account's extended private key, e.g. `zprv` for BIP84 bitcoin.
+pub fn private_prefix(coin: CoinType, purpose: Purpose) -> Prefix {
+    coin.adapter().private_prefix(purpose)
+}
+
+/// Whether `version` is the extended public key version of a supported coin and purpose.
+///
+/// bip32 only treats `?pub` prefixes as public, which misses e.g. `Ltub` and `dgub`.
+pub fn is_public_version(version: u32) -> bool {
+    key_versions().any(|(public, _)| public.version() == version)
+}
+
+/// Whether `version` is the extended private key version of a supported coin and purpose.
+pub fn is_private_version(version: u32) -> bool {
+    key_versions().any(|(_, private)| private.version() == version)
+}
+
+fn key_versions() -> impl Iterator<Item = (Prefix, Prefix)> {
+    COINS.iter().flat_map(|coin| {
+        let adapter = coin.adapter();
+        adapter.purposes().iter().map(move |purpose| {
+            (
+                adapter.public_prefix(*purpose),
+                adapter.private_prefix(*purpose),
+            )
+        })
+    })
+}
+
+/// Parses an extended public key in any SLIP-132 version the wallet exports.
+pub fn parse_xpub(extended_key: &str) -> Result<XPub> {
+    let mut key = ExtendedKey::from_str(extended_key.trim())?;
+    let version = key.prefix.version();
+    if is_private_version(version) {
+        return Err(Error::msg(NOT_AN_XPUB));
+    }
+    if !is_public_version(version) {
+        return Err(Error::msg(UNKNOWN_KEY_VERSION));
+    }
+    // the key material is the same in every version, bip32 only reads `xpub` as public
+    key.prefix = Prefix::XPUB;
+    Ok(XPub::try_from(key)?)
+}
+
+/// An account's extended key along with where it comes from, as other wallets import it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountExport {
+    pub extended_key: String,
+    /// Hex master key fingerprint, unknown for watch-only wallets
+    pub master_fingerprint: Option<String>,
+    /// `m/purpose'/coin'/account'`
+    pub origin_path: DerivationPath,
+}
+
+impl AccountExport {
+    /// `[73c5da0a/84h/0h/0h]`, unknown without a master fingerprint
+    pub fn key_origin(&self) -> Option<String> {
+        let fingerprint = self.master_fingerprint.as_ref()?;
+        let path = format!("{:#}", self.origin_path);
+        Some(format!("[{}{}]", fingerprint, path.trim_start_matches('m')))
+    }
+}
+
+/// Key origin form used by output descriptors, `[73c5da0a/84h/0h/0h]zpub...`
+impl fmt::Display for AccountExport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(origin) = self.key_origin() {
+            write!(f, "{}", origin)?;
+        }
+        write!(f, "{}", self.extended_key)
+    }
+}
@@ -0,0 +1,95 @@
+use super::core::CoinType;
+use super::export::AccountExport;
+use super::path::Purpose;
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+// ERR MESSAGES
+const INVALID_LABEL_RECORD: &str = "invalid BIP329 label record on line";
+
+/// Notes, tags and archived state of an account or address. They stay in the wallet file, BIP329
+/// has no field for them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Annotations {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Hidden from everyday views but still found and derived like any other entry
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub archived: bool,
+}
+
+impl Annotations {
+    pub fn is_empty(&self) -> bool {
+        *self == Annotations::default()
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// What a BIP329 record refers to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelType {
+    Tx,
+    Addr,
+    Pubkey,
+    Input,
+    Output,
+    Xpub,
+}
+
+/// One line of a BIP329 label export. Fields from later revisions of the BIP are ignored.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LabelRecord {
+    #[serde(rename = "type")]
+    pub label_type: LabelType,
+    #[serde(rename = "ref")]
+    pub reference: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Key origin in descriptor form, e.g. `wpkh([73c5da0a/84h/0h/0h])`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spendable: Option<bool>,
+}
+
+/// Reads BIP329 JSON Lines, blank lines are skipped.
+pub fn parse_labels(jsonl: &str) -> Result<Vec<LabelRecord>> {
+    jsonl
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                Error::msg(format!("{} {}: {}", INVALID_LABEL_RECORD, number + 1, err))
+            })
+        })
+        .collect()
+}
+
+/// Writes `records` as BIP329 JSON Lines.
+pub fn write_labels(records: &[LabelRecord]) -> Result<String> {
+    let mut jsonl = String::new();
+    for record in records {
+        jsonl.push_str(&serde_json::to_string(record)?);
+        jsonl.push('\n');
+    }
+    Ok(jsonl)
+}
+
+/// Descriptor form of where an account key comes from, only known for chains with descriptors
+/// and accounts with a master fingerprint.
+pub fn descriptor_origin(
+    coin: CoinType,
+    purpose: Purpose,
+    export: &AccountExport,
+) -> Option<String> {
+    let descriptor = coin.adapter().descriptor(purpose)?;
+    let close = ")".repeat(descriptor.matches('(').count() + 1);
+    Some(format!("{}({}{}", descriptor, export.key_origin()?, close))
+}
@@ -0,0 +1,131 @@
+use super::core::{Bip44Address, Bip44ChangeVal, CoinAccounts, CoinType};
+use super::path::{DerivationPath, Purpose};
+use std::collections::HashMap;
+
+/// Where an address stored in the wallet belongs and the path its key is derived at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressLocation {
+    pub coin: CoinType,
+    pub purpose: Purpose,
+    pub account: u32,
+    pub change: Bip44ChangeVal,
+    pub index: u32,
+    pub path: DerivationPath,
+    /// Matched the address an entry had before its path was migrated, `path` is the old path
+    pub legacy: bool,
+}
+
+/// Maps every stored address of one account tree to its `AddressLocation`.
+///
+/// Both the plain and the checksummed form are indexed, as well as the aliases the coin's adapter
+/// knows, e.g. legacy Bitcoin Cash addresses. EVM addresses also match in any case.
+///
+/// Test networks share slip44 1 and most of their encodings, so accounts on testnet, signet and
+/// regtest can hold the same address. Every location of an address is kept, in `location_order`.
+#[derive(Debug, Default, Clone)]
+pub struct AddressIndex {
+    exact: HashMap<String, Vec<AddressLocation>>,
+    // lowercase keys, only for coins whose addresses are case-insensitive
+    any_case: HashMap<String, Vec<AddressLocation>>,
+}
+
+impl AddressIndex {
+    pub fn new(coins: &HashMap<CoinType, CoinAccounts>) -> Self {
+        let mut index = AddressIndex::default();
+        for (coin, purposes) in coins {
+            for (purpose, accounts) in purposes {
+                for account in accounts.accounts.values() {
+                    for change in account.changes.values() {
+                        for (address_index, address) in &change.addresses {
+                            let location = AddressLocation {
+                                coin: *coin,
+                                purpose: *purpose,
+                                account: account.index,
+                                change: change.change,
+                                index: *address_index,
+                                path: address.path.clone(),
+                                legacy: false,
+                            };
+                            index.insert(address, location);
+                        }
+                    }
+                }
+            }
+        }
+        index
+    }
+
+    /// Indexes `address` and, if it was migrated, the address it had before.
+    pub fn insert(&mut self, address: &Bip44Address, location: AddressLocation) {
+        if let Some(legacy) = &address.legacy {
+            // paths from before the migration were written by this wallet and always parse
+            if let Ok(path) = legacy.path.parse() {
+                let legacy_location = AddressLocation {
+                    path,
+                    legacy: true,
+                    ..location.clone()
+                };
+                self.insert_form(&legacy.address, legacy_location);
+            }
+        }
+        self.insert_form(&address.address, location.clone());
+        self.insert_form(&address.address_checksummed, location);
+    }
+
+    fn insert_form(&mut self, address: &str, location: AddressLocation) {
+        for alias in location.coin.adapter().address_aliases(address) {
+            self.insert_alias(&alias, location.clone());
+        }
+        self.insert_alias(address, location);
+    }
+
+    fn insert_alias(&mut self, address: &str, location: AddressLocation) {
+        if location.coin.adapter().case_insensitive(address) {
+            add_location(
+                self.any_case.entry(address.to_lowercase()).or_default(),
+                location.clone(),
+            );
+        }
+        add_location(self.exact.entry(address.to_string()).or_default(), location);
+    }
+
+    /// Every location of `address`, empty if the wallet doesn't hold it
+    pub fn get(&self, address: &str) -> &[AddressLocation] {
+        let address = address.trim();
+        self.exact
+            .get(address)
+            .or_else(|| self.any_case.get(&address.to_lowercase()))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Number of distinct address strings indexed
+    pub fn len(&self) -> usize {
+        self.exact.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty()
+    }
+}
+
+// the plain and checksummed forms are often the same string, keep each location once
+fn add_location(locations: &mut Vec<AddressLocation>, location: AddressLocation) {
+    if !locations.contains(&location) {
+        locations.push(location);
+        locations.sort_by_key(location_order);
+    }
+}
+
+/// Order of the locations of an address shared by several accounts, by coin first so that e.g.
+/// testnet comes before signet
+pub fn location_order(location: &AddressLocation) -> (CoinType, u32, u32, u32, u32, bool) {
+    (
+        location.coin,
+        location.purpose as u32,
+        location.account,
+        location.change.index(),
+        location.index,
+        location.legacy,
+    )
+}
@@ -1,4 +1,17 @@
+pub mod bitcoin_cash;
+pub mod coins;
 pub mod core;
+pub mod cosmos;
+pub mod discovery;
+pub mod encryption;
 pub mod evm;
+pub mod evm_chains;
+pub mod export;
+pub mod labels;
+pub mod lookup;
+pub mod path;
+pub mod slip10;
+pub mod solana;
+pub mod tron;
 pub mod utils;
 pub mod wallet_bitcoin;
@@ -0,0 +1,240 @@
+use anyhow::{Error, Result};
+use bip32::ChildNumber;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+// ERR MESSAGES
+const MISSING_MASTER: &str = "derivation path must start with \"m\"";
+const UNHARDENED_KEY: &str = "must harden child key";
+const HARDENED_PUBLIC_LEVEL: &str = "change and address index must not be hardened";
+const INVALID_CHANGE: &str = "change level must be 0 (receiving) or 1 (internal)";
+const TOO_DEEP_FOR_PURPOSE: &str =
+    "purpose paths have at most 5 levels: purpose'/coin'/account'/change/index";
+
+/// Largest index that can be used before the hardened bit, 2^31 - 1
+pub const MAX_CHILD_INDEX: u32 = (1 << 31) - 1;
+
+/// One level of a derivation path.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ChildIndex {
+    pub index: u32,
+    pub hardened: bool,
+}
+
+impl ChildIndex {
+    pub fn normal(index: u32) -> Result<Self> {
+        ChildIndex::new(index, false)
+    }
+
+    pub fn hardened(index: u32) -> Result<Self> {
+        ChildIndex::new(index, true)
+    }
+
+    pub fn new(index: u32, hardened: bool) -> Result<Self> {
+        if index > MAX_CHILD_INDEX {
+            return Err(Error::msg(format!(
+                "child index {} is out of range, must be at most {}",
+                index, MAX_CHILD_INDEX
+            )));
+        }
+        Ok(ChildIndex { index, hardened })
+    }
+}
+
+/// Purpose levels with a standard `purpose'/coin'/account'/change/index` layout.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Purpose {
+    // legacy P2PKH and every non-bitcoin chain
+    Bip44 = 44,
+    // P2SH wrapped segwit
+    Bip49 = 49,
+    // native segwit
+    Bip84 = 84,
+    // taproot
+    Bip86 = 86,
+}
+
+impl Purpose {
+    pub fn from_index(index: u32) -> Option<Purpose> {
+        match index {
+            44 => Some(Purpose::Bip44),
+            49 => Some(Purpose::Bip49),
+            84 => Some(Purpose::Bip84),
+            86 => Some(Purpose::Bip86),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Purpose {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", *self as u32)
+    }
+}
+
+/// A BIP32 derivation path of any depth, e.g. `m/84'/0'/0'/0/5` or `m/83696968h/39h/0h`.
+///
+/// Parses both `'` and `h` as the hardened marker. `Display` prints `'`, the alternate form
+/// (`{:#}`) prints `h`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DerivationPath {
+    children: Vec<ChildIndex>,
+}
+
+impl DerivationPath {
+    /// The master key path, `m`
+    pub fn master() -> Self {
+        DerivationPath::default()
+    }
+
+    /// `m/purpose'/coin'/account'/change/index`
+    pub fn for_address(
+        purpose: Purpose,
+        coin: u32,
+        account: u32,
+        change: u32,
+        index: u32,
+    ) -> Result<Self> {
+        Ok(DerivationPath::for_account(purpose, coin, account)?
+            .child(ChildIndex::normal(change)?)
+            .child(ChildIndex::normal(index)?))
+    }
+
+    /// `m/purpose'/coin'/account'`
+    pub fn for_account(purpose: Purpose, coin: u32, account: u32) -> Result<Self> {
+        Ok(DerivationPath::master()
+            .child(ChildIndex::hardened(purpose as u32)?)
+            .child(ChildIndex::hardened(coin)?)
+            .child(ChildIndex::hardened(account)?))
+    }
+
+    /// Returns a copy of this path extended by one level.
+    pub fn child(&self, child: ChildIndex) -> Self {
+        let mut children = self.children.clone();
+        children.push(child);
+        DerivationPath { children }
+    }
+
+    pub fn children(&self) -> &[ChildIndex] {
+        &self.children
+    }
+
+    pub fn depth(&self) -> usize {
+        self.children.len()
+    }
+
+    /// The purpose level, if the first level is a hardened BIP44/49/84/86 purpose.
+    pub fn purpose(&self) -> Option<Purpose> {
+        self.children
+            .first()
+            .filter(|child| child.hardened)
+            .and_then(|child| Purpose::from_index(child.index))
+    }
+
+    /// Checks the hardening rules of the purpose layout. Paths without a known purpose, such
+    /// as BIP85 or SLIP-10 paths, are always accepted.
+    pub fn validate_purpose(&self) -> Result<()> {
+        if self.purpose().is_none() {
+            return Ok(());
+        }
+        if self.depth() > 5 {
+            return Err(Error::msg(TOO_DEEP_FOR_PURPOSE));
+        }
+        // purpose, coin type and account must be hardened
+        if self.children.iter().take(3).any(|child| !child.hardened) {
+            return Err(Error::msg(UNHARDENED_KEY));
+        }
+        // change and address index are public derivations so that xpubs can generate addresses
+        if self.children.iter().skip(3).any(|child| child.hardened) {
+            return Err(Error::msg(HARDENED_PUBLIC_LEVEL));
+        }
+        if let Some(change) = self.children.get(3) {
+            if change.index > 1 {
+                return Err(Error::msg(INVALID_CHANGE));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_bip32(&self) -> Result<bip32::DerivationPath> {
+        let mut path = bip32::DerivationPath::default();
+        for child in &self.children {
+            path.push(ChildNumber::new(child.index, child.hardened)?);
+        }
+        Ok(path)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let marker = if f.alternate() { "h" } else { "'" };
+        write!(f, "m")?;
+        for child in &self.children {
+            write!(f, "/{}", child.index)?;
+            if child.hardened {
+                write!(f, "{}", marker)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = Error;
+
+    fn from_str(path: &str) -> Result<Self> {
+        let mut parts = path.trim().split('/');
+        if parts.next() != Some("m") {
+            return Err(Error::msg(MISSING_MASTER));
+        }
+
+        let mut children = Vec::new();
+        for (position, part) in parts.enumerate() {
+            let level = position + 1;
+            let (digits, hardened) = match part.strip_suffix(['\'', 'h', 'H']) {
+                Some(digits) => (digits, true),
+                None => (part, false),
+            };
+            if digits.is_empty() {
+                return Err(Error::msg(format!(
+                    "missing child index at level {} of \"{}\"",
+                    level, path
+                )));
+            }
+            if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+                return Err(Error::msg(format!(
+                    "\"{}\" at level {} is not a child index, expected a number optionally followed by ' or h",
+                    part, level
+                )));
+            }
+            let index = digits
+                .parse::<u32>()
+                .ok()
+                .filter(|index| *index <= MAX_CHILD_INDEX);
+            match index {
+                Some(index) => children.push(ChildIndex { index, hardened }),
+                None => {
+                    return Err(Error::msg(format!(
+                        "child index {} at level {} is out of range, must be at most {}",
+                        digits, level, MAX_CHILD_INDEX
+                    )))
+                }
+            }
+        }
+        Ok(DerivationPath { children })
+    }
+}
+
+// stored as its string form so wallet files stay readable
+impl Serialize for DerivationPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DerivationPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let path = String::deserialize(deserializer)?;
+        path.parse().map_err(serde::de::Error::custom)
+    }
+}
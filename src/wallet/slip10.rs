@@ -0,0 +1,73 @@
+use super::path::DerivationPath;
+use anyhow::{Error, Result};
+use ed25519_dalek::SigningKey;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+
+// ERR MESSAGES
+const UNHARDENED_ED25519: &str = "ed25519 keys only support hardened derivation";
+
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// A SLIP-10 ed25519 private key with its chain code.
+///
+/// ed25519 has no public derivation, so every level of a path must be hardened and addresses
+/// can only be derived while the seed is available.
+pub struct Ed25519Key {
+    secret: [u8; 32],
+    chain_code: [u8; 32],
+}
+
+impl Ed25519Key {
+    /// The master key of a BIP39 seed
+    pub fn from_seed(seed: &[u8]) -> Result<Self> {
+        Ed25519Key::from_hmac(ED25519_SEED_KEY, &[seed])
+    }
+
+    /// Derives the key at `path`, whose levels must all be hardened.
+    pub fn derive_from_path(seed: &[u8], path: &DerivationPath) -> Result<Self> {
+        let mut key = Ed25519Key::from_seed(seed)?;
+        for child in path.children() {
+            if !child.hardened {
+                return Err(Error::msg(UNHARDENED_ED25519));
+            }
+            key = key.derive_hardened(child.index)?;
+        }
+        Ok(key)
+    }
+
+    /// Child `index'`, `index` is below the hardened bit.
+    pub fn derive_hardened(&self, index: u32) -> Result<Self> {
+        let index = (index | 1 << 31).to_be_bytes();
+        Ed25519Key::from_hmac(&self.chain_code, &[&[0], &self.secret, &index])
+    }
+
+    fn from_hmac(key: &[u8], data: &[&[u8]]) -> Result<Self> {
+        let mut mac = HmacSha512::new_from_slice(key)?;
+        for part in data {
+            mac.update(part);
+        }
+        let digest = mac.finalize().into_bytes();
+        let mut secret = [0; 32];
+        let mut chain_code = [0; 32];
+        secret.copy_from_slice(&digest[..32]);
+        chain_code.copy_from_slice(&digest[32..]);
+        Ok(Ed25519Key { secret, chain_code })
+    }
+
+    pub fn secret_key(&self) -> &[u8; 32] {
+        &self.secret
+    }
+
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        SigningKey::from_bytes(&self.secret)
+            .verifying_key()
+            .to_bytes()
+    }
+}
@@ -0,0 +1,80 @@
+use super::coins::{CoinAdapter, Curve, EncodedAddress};
+use super::core::{Bip44ChangeVal, CoinType, PubKeyForm};
+use super::path::{ChildIndex, DerivationPath, Purpose};
+use anyhow::{Error, Result};
+
+// ERR MESSAGES
+const INVALID_ADDRESS: &str = "expected a base58 encoded 32 byte public key";
+const INVALID_PUB_KEY: &str = "expected a 32 byte ed25519 public key";
+const SINGLE_ADDRESS: &str = "solana accounts have a single address, create a new account instead";
+
+/// `CoinAdapter` of Solana, addresses are the base58 encoded ed25519 public key.
+///
+/// Follows Phantom and Solflare: account `n` is the single address at `m/44'/501'/n'/0'`.
+pub struct SolanaAdapter;
+
+impl CoinAdapter for SolanaAdapter {
+    fn coin(&self) -> CoinType {
+        CoinType::Solana
+    }
+
+    fn slip44(&self) -> u32 {
+        501
+    }
+
+    fn curve(&self) -> Curve {
+        Curve::Ed25519
+    }
+
+    fn purposes(&self) -> &'static [Purpose] {
+        &[Purpose::Bip44]
+    }
+
+    /// `m/44'/501'/account'/change'`, every level hardened as SLIP-10 ed25519 requires
+    fn address_path(
+        &self,
+        purpose: Purpose,
+        account: u32,
+        change: Bip44ChangeVal,
+        index: u32,
+    ) -> Result<DerivationPath> {
+        if index >= self.addresses_per_chain() {
+            return Err(Error::msg(SINGLE_ADDRESS));
+        }
+        Ok(
+            DerivationPath::for_account(purpose, self.slip44(), account)?
+                .child(ChildIndex::hardened(change.index())?),
+        )
+    }
+
+    fn addresses_per_chain(&self) -> u32 {
+        1
+    }
+
+    fn encode_address(
+        &self,
+        pub_key: &[u8],
+        _purpose: Option<Purpose>,
+        _key_form: PubKeyForm,
+    ) -> Result<EncodedAddress> {
+        if pub_key.len() != 32 {
+            return Err(Error::msg(INVALID_PUB_KEY));
+        }
+        let address = bs58::encode(pub_key).into_string();
+        Ok(EncodedAddress {
+            checksummed: address.clone(),
+            address,
+            // ed25519 keys have a single serialisation
+            key_form: PubKeyForm::Compressed,
+        })
+    }
+
+    /// Solana addresses carry no checksum, any base58 string of 32 bytes is accepted.
+    fn checksum_address(&self, address: &str) -> Result<String> {
+        let address = address.trim();
+        match bs58::decode(address).into_vec() {
+            Ok(bytes) if bytes.len() == 32 => Ok(address.to_string()),
+            _ => Err(Error::msg(INVALID_ADDRESS)),
+        }
+    }
+}
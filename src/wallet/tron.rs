@@ -0,0 +1,62 @@
+use super::coins::{CoinAdapter, Curve, EncodedAddress};
+use super::core::{CoinType, PubKeyForm};
+use super::evm::address_from_pubkey;
+use super::path::Purpose;
+use anyhow::{Error, Result};
+use secp256k1::PublicKey;
+
+// ERR MESSAGES
+const INVALID_ADDRESS: &str = "expected a base58check address with the 0x41 prefix";
+
+/// Version byte of mainnet addresses, every address starts with `T`
+const ADDRESS_PREFIX: u8 = 0x41;
+
+/// `CoinAdapter` of Tron, the EVM address of the key base58check encoded behind `0x41`.
+pub struct TronAdapter;
+
+impl CoinAdapter for TronAdapter {
+    fn coin(&self) -> CoinType {
+        CoinType::Tron
+    }
+
+    fn slip44(&self) -> u32 {
+        195
+    }
+
+    fn curve(&self) -> Curve {
+        Curve::Secp256k1
+    }
+
+    fn purposes(&self) -> &'static [Purpose] {
+        &[Purpose::Bip44]
+    }
+
+    fn encode_address(
+        &self,
+        pub_key: &[u8],
+        _purpose: Option<Purpose>,
+        _key_form: PubKeyForm,
+    ) -> Result<EncodedAddress> {
+        let pub_key = PublicKey::from_slice(pub_key)?.serialize_uncompressed();
+        let mut payload = vec![ADDRESS_PREFIX];
+        payload.extend_from_slice(address_from_pubkey(pub_key).as_bytes());
+        let address = bs58::encode(payload).with_check().into_string();
+        Ok(EncodedAddress {
+            checksummed: address.clone(),
+            address,
+            // keccak of the uncompressed key, like ethereum
+            key_form: PubKeyForm::Uncompressed,
+        })
+    }
+
+    fn checksum_address(&self, address: &str) -> Result<String> {
+        let address = address.trim();
+        match bs58::decode(address)
+            .with_check(Some(ADDRESS_PREFIX))
+            .into_vec()
+        {
+            Ok(payload) if payload.len() == 21 => Ok(address.to_string()),
+            _ => Err(Error::msg(INVALID_ADDRESS)),
+        }
+    }
+}
@@ -1,7 +1,3 @@
-use rand::rngs::OsRng;
-use secp256k1::{PublicKey, Secp256k1, SecretKey};
-use tiny_keccak::keccak256;
-use web3::types::Address;
 use web3::types::U256;
 
 pub fn wei_to_eth(wei_val: U256) -> f64 {
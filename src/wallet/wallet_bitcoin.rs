@@ -1,10 +1,304 @@
+use super::coins::{CoinAdapter, Curve, EncodedAddress};
+use super::core::{CoinType, PubKeyForm};
+use super::path::Purpose;
 use anyhow::{Error, Result};
-use bitcoin::{address::Address, Network, PublicKey as PubKeyStructBitcoin};
-use secp256k1::{Parity, PublicKey, XOnlyPublicKey};
+use bip32::Prefix;
+use bitcoin::address::{AddressEncoding, Payload, WitnessProgram, WitnessVersion};
+use bitcoin::bech32::{self, FromBase32};
+use bitcoin::hashes::Hash;
+use bitcoin::ScriptHash;
+use bitcoin::{address::Address, base58, Network, PubkeyHash, PublicKey as PubKeyStructBitcoin};
+use secp256k1::{Parity, PublicKey, Secp256k1, XOnlyPublicKey};
 
 const INVALID_PARITY_ON_COMPRESSED_KEY: &str = "invalid parity on compressed key";
+const INVALID_COMPRESSED_KEY: &str = "expected a 33 byte compressed public key";
+const UNCOMPRESSED_NOT_LEGACY: &str =
+    "uncompressed keys are only used for legacy (BIP44) bitcoin addresses";
+const WRONG_CHAIN: &str = "address belongs to a different chain";
+const INVALID_WITNESS_PROGRAM: &str = "segwit address has no witness version";
 
+/// Address versions and SLIP-132 key versions of a bitcoin-like chain. Litecoin, Dogecoin and
+/// the test networks derive their addresses exactly like bitcoin and only differ in these.
+#[derive(Debug, Clone, Copy)]
+pub struct ChainParams {
+    pub p2pkh_prefix: u8,
+    pub p2sh_prefix: u8,
+    /// Prefix of segwit addresses, `None` for chains without segwit
+    pub bech32_hrp: Option<&'static str>,
+    /// The `bitcoin` crate network for bitcoin itself and its test networks
+    pub network: Option<Network>,
+    pub purposes: &'static [Purpose],
+    /// (public, private) versions of BIP44, BIP49 and BIP84 accounts. Taproot has no
+    /// registered version so BIP86 accounts use the BIP44 ones, like other wallets do.
+    pub key_versions: [(Prefix, Prefix); 3],
+}
+
+const ALL_PURPOSES: &[Purpose] = &[
+    Purpose::Bip44,
+    Purpose::Bip49,
+    Purpose::Bip84,
+    Purpose::Bip86,
+];
+
+pub const BITCOIN: ChainParams = ChainParams {
+    p2pkh_prefix: 0x00,
+    p2sh_prefix: 0x05,
+    bech32_hrp: Some("bc"),
+    network: Some(Network::Bitcoin),
+    purposes: ALL_PURPOSES,
+    key_versions: [
+        (Prefix::XPUB, Prefix::XPRV),
+        (Prefix::YPUB, Prefix::YPRV),
+        (Prefix::ZPUB, Prefix::ZPRV),
+    ],
+};
+
+pub const BITCOIN_TESTNET: ChainParams = ChainParams {
+    p2pkh_prefix: 0x6f,
+    p2sh_prefix: 0xc4,
+    bech32_hrp: Some("tb"),
+    network: Some(Network::Testnet),
+    purposes: ALL_PURPOSES,
+    key_versions: [
+        (Prefix::TPUB, Prefix::TPRV),
+        // SLIP-132 versions bip32 has no constants for
+        (
+            Prefix::from_parts_unchecked("upub", 0x044a5262),
+            Prefix::from_parts_unchecked("uprv", 0x044a4e28),
+        ),
+        (
+            Prefix::from_parts_unchecked("vpub", 0x045f1cf6),
+            Prefix::from_parts_unchecked("vprv", 0x045f18bc),
+        ),
+    ],
+};
+
+/// Signet addresses and keys are encoded exactly like testnet ones
+pub const BITCOIN_SIGNET: ChainParams = ChainParams {
+    network: Some(Network::Signet),
+    ..BITCOIN_TESTNET
+};
+
+/// Local regtest nodes, testnet versions but segwit addresses start with `bcrt1`
+pub const BITCOIN_REGTEST: ChainParams = ChainParams {
+    bech32_hrp: Some("bcrt"),
+    network: Some(Network::Regtest),
+    ..BITCOIN_TESTNET
+};
+
+pub const LITECOIN: ChainParams = ChainParams {
+    p2pkh_prefix: 0x30,
+    // `M...`, the old `3...` prefix clashed with bitcoin
+    p2sh_prefix: 0x32,
+    bech32_hrp: Some("ltc"),
+    network: None,
+    purposes: &[Purpose::Bip44, Purpose::Bip49, Purpose::Bip84],
+    key_versions: [
+        (
+            Prefix::from_parts_unchecked("Ltub", 0x019da462),
+            Prefix::from_parts_unchecked("Ltpv", 0x019d9cfe),
+        ),
+        (
+            Prefix::from_parts_unchecked("Mtub", 0x01b26ef6),
+            Prefix::from_parts_unchecked("Mtpv", 0x01b26792),
+        ),
+        // what Trezor and Electrum-LTC use for native segwit
+        (Prefix::ZPUB, Prefix::ZPRV),
+    ],
+};
+
+pub const DOGECOIN: ChainParams = ChainParams {
+    p2pkh_prefix: 0x1e,
+    p2sh_prefix: 0x16,
+    bech32_hrp: None,
+    network: None,
+    purposes: &[Purpose::Bip44],
+    key_versions: [
+        (
+            Prefix::from_parts_unchecked("dgub", 0x02facafd),
+            Prefix::from_parts_unchecked("dgpv", 0x02fac398),
+        ),
+        (Prefix::XPUB, Prefix::XPRV),
+        (Prefix::XPUB, Prefix::XPRV),
+    ],
+};
+
+/// Legacy addresses of Bitcoin Cash, which kept bitcoin's version bytes. Its `CoinAdapter`
+/// stores CashAddr instead, see `bitcoin_cash`.
+pub const BITCOIN_CASH: ChainParams = ChainParams {
+    p2pkh_prefix: 0x00,
+    p2sh_prefix: 0x05,
+    bech32_hrp: None,
+    network: None,
+    purposes: &[Purpose::Bip44],
+    key_versions: [
+        (Prefix::XPUB, Prefix::XPRV),
+        (Prefix::XPUB, Prefix::XPRV),
+        (Prefix::XPUB, Prefix::XPRV),
+    ],
+};
+
+/// `CoinAdapter` of bitcoin and every chain that only differs from it in `ChainParams`.
+pub struct BitcoinAdapter {
+    coin: CoinType,
+    slip44: u32,
+    params: ChainParams,
+}
+
+impl BitcoinAdapter {
+    pub const fn new(coin: CoinType, slip44: u32, params: ChainParams) -> Self {
+        BitcoinAdapter {
+            coin,
+            slip44,
+            params,
+        }
+    }
+
+    /// Encodes `payload` with the version bytes and prefix of this chain.
+    pub fn encode_payload(&self, payload: &Payload) -> String {
+        AddressEncoding {
+            payload,
+            p2pkh_prefix: self.params.p2pkh_prefix,
+            p2sh_prefix: self.params.p2sh_prefix,
+            // segwit payloads are only built for purposes of chains that have a prefix
+            bech32_hrp: self.params.bech32_hrp.unwrap_or_default(),
+        }
+        .to_string()
+    }
+
+    /// Parses an address of this chain back into its payload.
+    pub fn decode_payload(&self, address: &str) -> Result<Payload> {
+        let address = address.trim();
+        if let Some(hrp) = self.params.bech32_hrp {
+            let (decoded_hrp, data, variant) = match bech32::decode(address) {
+                Ok(decoded) => decoded,
+                Err(_) => return self.decode_base58(address),
+            };
+            if decoded_hrp != hrp {
+                return Err(Error::msg(WRONG_CHAIN));
+            }
+            let (version, program) = data
+                .split_first()
+                .ok_or(Error::msg(INVALID_WITNESS_PROGRAM))?;
+            let version = WitnessVersion::try_from(*version)?;
+            if variant != version.bech32_variant() {
+                return Err(Error::msg(INVALID_WITNESS_PROGRAM));
+            }
+            let program = Vec::<u8>::from_base32(program)?;
+            return Ok(Payload::WitnessProgram(WitnessProgram::new(
+                version, program,
+            )?));
+        }
+        self.decode_base58(address)
+    }
+
+    fn decode_base58(&self, address: &str) -> Result<Payload> {
+        let data = base58::decode_check(address)?;
+        match data.split_first() {
+            Some((prefix, hash)) if *prefix == self.params.p2pkh_prefix => {
+                Ok(Payload::PubkeyHash(PubkeyHash::from_slice(hash)?))
+            }
+            Some((prefix, hash)) if *prefix == self.params.p2sh_prefix => {
+                Ok(Payload::ScriptHash(ScriptHash::from_slice(hash)?))
+            }
+            _ => Err(Error::msg(WRONG_CHAIN)),
+        }
+    }
+
+    fn key_versions(&self, purpose: Purpose) -> (Prefix, Prefix) {
+        match purpose {
+            Purpose::Bip49 => self.params.key_versions[1],
+            Purpose::Bip84 => self.params.key_versions[2],
+            Purpose::Bip44 | Purpose::Bip86 => self.params.key_versions[0],
+        }
+    }
+}
+
+impl CoinAdapter for BitcoinAdapter {
+    fn coin(&self) -> CoinType {
+        self.coin
+    }
+
+    fn slip44(&self) -> u32 {
+        self.slip44
+    }
+
+    fn curve(&self) -> Curve {
+        Curve::Secp256k1
+    }
+
+    fn purposes(&self) -> &'static [Purpose] {
+        self.params.purposes
+    }
+
+    fn encode_address(
+        &self,
+        pub_key: &[u8],
+        purpose: Option<Purpose>,
+        key_form: PubKeyForm,
+    ) -> Result<EncodedAddress> {
+        let pub_key: [u8; 33] = pub_key
+            .try_into()
+            .map_err(|_| Error::msg(INVALID_COMPRESSED_KEY))?;
+        // the payload is the same on every network, only its encoding differs
+        let address = match (key_form, purpose) {
+            (PubKeyForm::Compressed, purpose) => {
+                address_for_purpose(pub_key, purpose, Network::Bitcoin)?
+            }
+            (PubKeyForm::Uncompressed, Some(Purpose::Bip44)) => {
+                uncompressed_address_from_compressed_pub_key(pub_key, Network::Bitcoin)?
+            }
+            (PubKeyForm::Uncompressed, _) => return Err(Error::msg(UNCOMPRESSED_NOT_LEGACY)),
+        };
+        let address = self.encode_payload(&address.payload);
+        Ok(EncodedAddress {
+            address: address.clone(),
+            // unlike ethereum, bitcoin addresses are checksummed by default
+            checksummed: address,
+            key_form,
+        })
+    }
+
+    fn checksum_address(&self, address: &str) -> Result<String> {
+        Ok(self.encode_payload(&self.decode_payload(address)?))
+    }
+
+    fn bitcoin_network(&self) -> Option<Network> {
+        self.params.network
+    }
+
+    fn public_prefix(&self, purpose: Purpose) -> Prefix {
+        self.key_versions(purpose).0
+    }
+
+    fn private_prefix(&self, purpose: Purpose) -> Prefix {
+        self.key_versions(purpose).1
+    }
+
+    fn descriptor(&self, purpose: Purpose) -> Option<&'static str> {
+        Some(match purpose {
+            Purpose::Bip44 => "pkh",
+            Purpose::Bip49 => "sh(wpkh",
+            Purpose::Bip84 => "wpkh",
+            Purpose::Bip86 => "tr",
+        })
+    }
+}
+
+/// Legacy (P2PKH) address hashing the compressed key, `1...` on mainnet and `m...`/`n...` on
+/// testnet. This is what other BIP44 wallets derive.
 pub fn address_from_compressed_pub_key(pub_key: [u8; 33], network: Network) -> Result<Address> {
+    let pub_struct = PubKeyStructBitcoin::from_slice(&pub_key)?;
+    Ok(Address::p2pkh(&pub_struct, network))
+}
+
+/// P2PKH address hashing the uncompressed form of the key.
+///
+/// Earlier versions of this wallet handed these out, only use it to recover funds sent to them.
+pub fn uncompressed_address_from_compressed_pub_key(
+    pub_key: [u8; 33],
+    network: Network,
+) -> Result<Address> {
     let key_parity = parity_from_u8(pub_key[0])?;
     let key_x_val = XOnlyPublicKey::from_slice(&pub_key[1..33])?;
     let formatted_key = PublicKey::from_x_only_public_key(key_x_val, key_parity);
@@ -16,6 +310,55 @@ pub fn address_from_compressed_pub_key(pub_key: [u8; 33], network: Network) -> R
     Ok(Address::p2pkh(&pub_struct, network))
 }
 
+/// Native segwit (BIP84) address, `bc1q...` on mainnet and `tb1q...` on testnet.
+pub fn p2wpkh_address_from_compressed_pub_key(
+    pub_key: [u8; 33],
+    network: Network,
+) -> Result<Address> {
+    let pub_struct = PubKeyStructBitcoin::from_slice(&pub_key)?;
+    Ok(Address::p2wpkh(&pub_struct, network)?)
+}
+
+/// Nested segwit (BIP49) address, `3...` on mainnet and `2...` on testnet.
+pub fn p2shwpkh_address_from_compressed_pub_key(
+    pub_key: [u8; 33],
+    network: Network,
+) -> Result<Address> {
+    let pub_struct = PubKeyStructBitcoin::from_slice(&pub_key)?;
+    Ok(Address::p2shwpkh(&pub_struct, network)?)
+}
+
+/// Key path only taproot (BIP86) address, `bc1p...` on mainnet and `tb1p...` on testnet.
+///
+/// The output key is the x-only key tweaked with an empty script tree, as BIP341 requires.
+pub fn p2tr_address_from_compressed_pub_key(
+    pub_key: [u8; 33],
+    network: Network,
+) -> Result<Address> {
+    let secp = Secp256k1::verification_only();
+    let internal_key = XOnlyPublicKey::from_slice(&pub_key[1..33])?;
+    Ok(Address::p2tr(&secp, internal_key, None, network))
+}
+
+/// The untweaked x-only key, i.e. the compressed key without its parity byte.
+pub fn x_only_pub_key(pub_key: [u8; 33]) -> Result<[u8; 32]> {
+    Ok(XOnlyPublicKey::from_slice(&pub_key[1..33])?.serialize())
+}
+
+/// Picks the address type that goes with the purpose level of the derivation path.
+pub fn address_for_purpose(
+    pub_key: [u8; 33],
+    purpose: Option<Purpose>,
+    network: Network,
+) -> Result<Address> {
+    match purpose {
+        Some(Purpose::Bip49) => p2shwpkh_address_from_compressed_pub_key(pub_key, network),
+        Some(Purpose::Bip84) => p2wpkh_address_from_compressed_pub_key(pub_key, network),
+        Some(Purpose::Bip86) => p2tr_address_from_compressed_pub_key(pub_key, network),
+        _ => address_from_compressed_pub_key(pub_key, network),
+    }
+}
+
 fn parity_from_u8(int: u8) -> Result<Parity, Error> {
     match int {
         2 => Ok(Parity::Even),
@@ -0,0 +1,100 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use cryptowallet::wallet::bitcoin_cash::{decode_cashaddr, encode_cashaddr};
+    use cryptowallet::wallet::coins::{adapter, coins_for_address};
+    use cryptowallet::wallet::core::{CoinType, NewAddressParams, Wallet};
+    use cryptowallet::wallet::path::Purpose;
+
+    use crate::common::{temp_wallet_path, ZERO_ENTROPY_PHRASE_12};
+
+    // legacy and CashAddr pairs from the CashAddr specification
+    const SPEC_ADDRESSES: [(&str, &str); 4] = [
+        (
+            "1BpEi6DfDAUFd7GtittLSdBeYJvcoaVggu",
+            "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a",
+        ),
+        (
+            "1KXrWXciRDZUpQwQmuM1DbwsKDLYAYsVLR",
+            "bitcoincash:qr95sy3j9xwd2ap32xkykttr4cvcu7as4y0qverfuy",
+        ),
+        (
+            "16w1D5WRVKJuZUsSRzdLp9w3YGcgoxDXb",
+            "bitcoincash:qqq3728yw0y47sqn6l2na30mcw6zm78dzqre909m2r",
+        ),
+        (
+            "3CWFddi6m4ndiGyKqzYvsFYagqDLPVMTzC",
+            "bitcoincash:ppm2qsznhks23z7629mms6s4cwef74vcwvn0h829pq",
+        ),
+    ];
+
+    #[test]
+    fn cashaddr_matches_the_specification() {
+        let bitcoin_cash = adapter(CoinType::BitcoinCash);
+        for (legacy, cashaddr) in SPEC_ADDRESSES {
+            assert_eq!(bitcoin_cash.checksum_address(legacy).unwrap(), cashaddr);
+            assert_eq!(bitcoin_cash.checksum_address(cashaddr).unwrap(), cashaddr);
+            assert_eq!(
+                bitcoin_cash
+                    .checksum_address(&cashaddr.to_uppercase())
+                    .unwrap(),
+                cashaddr
+            );
+            let unprefixed = cashaddr.strip_prefix("bitcoincash:").unwrap();
+            assert_eq!(bitcoin_cash.checksum_address(unprefixed).unwrap(), cashaddr);
+            let payload = decode_cashaddr(cashaddr).unwrap();
+            assert_eq!(encode_cashaddr(&payload).unwrap(), cashaddr);
+        }
+        assert!(decode_cashaddr("bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6b").is_err());
+        assert!(decode_cashaddr("bchtest:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a").is_err());
+        assert!(decode_cashaddr("bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvY22gdx6a").is_err());
+
+        let (legacy, cashaddr) = SPEC_ADDRESSES[0];
+        assert_eq!(
+            coins_for_address(legacy),
+            [CoinType::Bitcoin, CoinType::BitcoinCash]
+        );
+        assert_eq!(coins_for_address(cashaddr), [CoinType::BitcoinCash]);
+    }
+
+    #[test]
+    fn bitcoin_cash_accounts_use_cashaddr() {
+        let path = temp_wallet_path("bitcoin_cash");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        wallet
+            .new_account(CoinType::BitcoinCash, Purpose::Bip44, "BCH")
+            .unwrap();
+        assert!(wallet
+            .new_account(CoinType::BitcoinCash, Purpose::Bip84, "segwit")
+            .is_err());
+        let addresses = wallet
+            .new_addresses(NewAddressParams::new(CoinType::BitcoinCash, 0), 1)
+            .unwrap();
+
+        let address = &addresses[0];
+        assert_eq!(address.path.to_string(), "m/44'/145'/0'/0/0");
+        assert!(address.address.starts_with("bitcoincash:q"));
+        assert_eq!(address.address_checksummed, address.address);
+
+        let bitcoin_cash = adapter(CoinType::BitcoinCash);
+        let legacy = &bitcoin_cash.address_aliases(&address.address)[0];
+        assert!(legacy.starts_with('1'));
+        for form in [
+            address.address.clone(),
+            address.address.to_uppercase(),
+            legacy.clone(),
+            address.address.replace("bitcoincash:", ""),
+        ] {
+            let location = wallet.find_address(&form).unwrap();
+            assert_eq!(location.coin, CoinType::BitcoinCash);
+            assert_eq!(location.index, 0);
+        }
+        // base58 is case sensitive, a legacy address in another case is a different address
+        assert!(wallet.find_address(&legacy.to_lowercase()).is_none());
+        assert!(wallet.find_address(&legacy.to_uppercase()).is_none());
+        assert!(!bitcoin_cash.case_insensitive(legacy));
+        assert!(bitcoin_cash.case_insensitive(&address.address.to_uppercase()));
+    }
+}
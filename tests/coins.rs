@@ -0,0 +1,211 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{Address, Network, PublicKey};
+    use cryptowallet::wallet::coins::{adapter, bitcoin_coin, coins_for_address, Curve, COINS};
+    use cryptowallet::wallet::core::{CoinType, PubKeyForm};
+    use cryptowallet::wallet::path::Purpose;
+
+    use crate::common::pub_key;
+
+    #[test]
+    fn every_coin_has_an_adapter() {
+        for coin in COINS {
+            let adapter = adapter(*coin);
+            assert_eq!(adapter.coin(), *coin);
+            let ed25519 = *coin == CoinType::Solana;
+            assert_eq!(adapter.curve() == Curve::Ed25519, ed25519);
+            assert_eq!(adapter.purposes()[0], Purpose::Bip44);
+        }
+        assert_eq!(CoinType::Bitcoin.slip44(), 0);
+        assert_eq!(CoinType::BitcoinTestnet.slip44(), 1);
+        assert_eq!(CoinType::Litecoin.slip44(), 2);
+        assert_eq!(CoinType::Dogecoin.slip44(), 3);
+        assert_eq!(CoinType::Ethereum.to_string(), "60");
+        assert!(!CoinType::Ethereum.supports_purpose(Purpose::Bip84));
+        assert!(CoinType::Litecoin.supports_purpose(Purpose::Bip84));
+        assert!(!CoinType::Litecoin.supports_purpose(Purpose::Bip86));
+        assert!(!CoinType::Dogecoin.supports_purpose(Purpose::Bip49));
+    }
+
+    #[test]
+    fn bitcoin_adapters_encode_for_their_network() {
+        let mainnet = adapter(CoinType::Bitcoin)
+            .encode_address(
+                &pub_key("m/84'/0'/0'/0/0"),
+                Some(Purpose::Bip84),
+                PubKeyForm::Compressed,
+            )
+            .unwrap();
+        assert_eq!(
+            mainnet.address,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(mainnet.checksummed, mainnet.address);
+
+        let testnet = adapter(CoinType::BitcoinTestnet)
+            .encode_address(
+                &pub_key("m/84'/1'/0'/0/0"),
+                Some(Purpose::Bip84),
+                PubKeyForm::Compressed,
+            )
+            .unwrap();
+        assert!(testnet.address.starts_with("tb1q"));
+
+        assert!(adapter(CoinType::Bitcoin)
+            .encode_address(
+                &pub_key("m/84'/0'/0'/0/0"),
+                Some(Purpose::Bip84),
+                PubKeyForm::Uncompressed,
+            )
+            .is_err());
+        assert!(adapter(CoinType::Bitcoin)
+            .encode_address(&[2; 20], None, PubKeyForm::Compressed)
+            .is_err());
+    }
+
+    #[test]
+    fn signet_and_regtest_encode_like_the_bitcoin_crate() {
+        for network in [
+            Network::Bitcoin,
+            Network::Testnet,
+            Network::Signet,
+            Network::Regtest,
+        ] {
+            let coin = bitcoin_coin(network).unwrap();
+            assert_eq!(adapter(coin).bitcoin_network(), Some(network));
+            let key = pub_key(&format!("m/84'/{}'/0'/0/0", coin.slip44()));
+            let expected = Address::p2wpkh(&PublicKey::from_slice(&key).unwrap(), network)
+                .unwrap()
+                .to_string();
+            let encoded = adapter(coin)
+                .encode_address(&key, Some(Purpose::Bip84), PubKeyForm::Compressed)
+                .unwrap();
+            assert_eq!(encoded.address, expected);
+        }
+        assert_eq!(
+            bitcoin_coin(Network::Regtest),
+            Some(CoinType::BitcoinRegtest)
+        );
+        assert_eq!(CoinType::BitcoinSignet.slip44(), 1);
+        assert_eq!(CoinType::BitcoinRegtest.slip44(), 1);
+
+        let regtest = adapter(CoinType::BitcoinRegtest);
+        let address = regtest
+            .encode_address(
+                &pub_key("m/84'/1'/0'/0/0"),
+                Some(Purpose::Bip84),
+                PubKeyForm::Compressed,
+            )
+            .unwrap()
+            .address;
+        assert!(address.starts_with("bcrt1q"));
+        assert_eq!(coins_for_address(&address), [CoinType::BitcoinRegtest]);
+        assert!(!regtest.validate_address("tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl"));
+        // legacy addresses are shared by every test network
+        let legacy = regtest
+            .encode_address(
+                &pub_key("m/44'/1'/0'/0/0"),
+                Some(Purpose::Bip44),
+                PubKeyForm::Compressed,
+            )
+            .unwrap()
+            .address;
+        assert_eq!(coins_for_address(&legacy).len(), 3);
+    }
+
+    #[test]
+    fn litecoin_and_dogecoin_match_reference_wallets() {
+        let address = |coin: CoinType, purpose: Purpose, path: &str| {
+            adapter(coin)
+                .encode_address(&pub_key(path), Some(purpose), PubKeyForm::Compressed)
+                .unwrap()
+                .address
+        };
+        assert_eq!(
+            address(CoinType::Litecoin, Purpose::Bip44, "m/44'/2'/0'/0/0"),
+            "LUWPbpM43E2p7ZSh8cyTBEkvpHmr3cB8Ez"
+        );
+        assert_eq!(
+            address(CoinType::Litecoin, Purpose::Bip49, "m/49'/2'/0'/0/0"),
+            "M7wtsL7wSHDBJVMWWhtQfTMSYYkyooAAXM"
+        );
+        assert_eq!(
+            address(CoinType::Litecoin, Purpose::Bip84, "m/84'/2'/0'/0/0"),
+            "ltc1qjmxnz78nmc8nq77wuxh25n2es7rzm5c2rkk4wh"
+        );
+        assert_eq!(
+            address(CoinType::Dogecoin, Purpose::Bip44, "m/44'/3'/0'/0/0"),
+            "DBus3bamQjgJULBJtYXpEzDWQRwF5iwxgC"
+        );
+
+        let litecoin = adapter(CoinType::Litecoin);
+        assert_eq!(
+            litecoin
+                .checksum_address("LTC1QJMXNZ78NMC8NQ77WUXH25N2ES7RZM5C2RKK4WH")
+                .unwrap(),
+            "ltc1qjmxnz78nmc8nq77wuxh25n2es7rzm5c2rkk4wh"
+        );
+        assert!(!litecoin.validate_address("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"));
+        assert!(!litecoin.validate_address("DBus3bamQjgJULBJtYXpEzDWQRwF5iwxgC"));
+        assert!(!litecoin.validate_address("LUWPbpM43E2p7ZSh8cyTBEkvpHmr3cB8Ey"));
+    }
+
+    #[test]
+    fn ethereum_addresses_are_eip55_checksummed() {
+        let ethereum = adapter(CoinType::Ethereum);
+        let encoded = ethereum
+            .encode_address(
+                &pub_key("m/44'/60'/0'/0/0"),
+                Some(Purpose::Bip44),
+                PubKeyForm::Compressed,
+            )
+            .unwrap();
+        assert_eq!(
+            encoded.address,
+            "0x9858effd232b4033e47d90003d41ec34ecaeda94"
+        );
+        assert_eq!(
+            encoded.checksummed,
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94"
+        );
+        assert_eq!(encoded.key_form, PubKeyForm::Uncompressed);
+
+        for form in [
+            "0x9858effd232b4033e47d90003d41ec34ecaeda94",
+            "0x9858EFFD232B4033E47D90003D41EC34ECAEDA94",
+            " 0x9858EfFD232B4033E47d90003D41EC34EcaEda94\n",
+        ] {
+            assert_eq!(
+                ethereum.checksum_address(form).unwrap(),
+                encoded.checksummed
+            );
+        }
+        // one letter with the wrong case
+        assert!(!ethereum.validate_address("0x9858efFD232B4033E47d90003D41EC34EcaEda94"));
+        assert!(!ethereum.validate_address("0x9858effd232b4033e47d90003d41ec34ecaeda"));
+        assert!(!ethereum.validate_address("9858effd232b4033e47d90003d41ec34ecaeda94"));
+    }
+
+    #[test]
+    fn addresses_are_matched_to_their_coins() {
+        assert_eq!(
+            coins_for_address("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"),
+            [CoinType::Bitcoin]
+        );
+        assert_eq!(
+            coins_for_address("tb1q6rz28mcfaxtmd6v789l9rrlrusdprr9pqcpvkl"),
+            [CoinType::BitcoinTestnet, CoinType::BitcoinSignet]
+        );
+        assert_eq!(
+            coins_for_address("0x9858EfFD232B4033E47d90003D41EC34EcaEda94"),
+            [CoinType::Ethereum]
+        );
+        assert_eq!(
+            coins_for_address("DBus3bamQjgJULBJtYXpEzDWQRwF5iwxgC"),
+            [CoinType::Dogecoin]
+        );
+        assert!(coins_for_address("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyv").is_empty());
+    }
+}
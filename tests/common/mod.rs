@@ -0,0 +1,50 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use bip32::{PublicKey as _, Seed};
+use cryptowallet::wallet::core::derive_child;
+use std::{
+    env, fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// The "abandon ... about" test mnemonic, 16 zero bytes of entropy
+pub const ZERO_ENTROPY_PHRASE_12: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+/// Wallet file in the temp directory, removed when dropped so a failing test doesn't leave it
+/// behind
+pub struct TempWalletPath(PathBuf);
+
+pub fn temp_wallet_path(name: &str) -> TempWalletPath {
+    TempWalletPath(env::temp_dir().join(format!("wolet_{}_{}.json", std::process::id(), name)))
+}
+
+impl Deref for TempWalletPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempWalletPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempWalletPath {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Compressed public key of the test mnemonic at `path`
+pub fn pub_key(path: &str) -> [u8; 33] {
+    let mnemonic = bip39::Mnemonic::parse(ZERO_ENTROPY_PHRASE_12).unwrap();
+    let seed = Seed::new(mnemonic.to_seed(""));
+    let key = derive_child(&seed, &path.parse().unwrap()).unwrap();
+    key.pub_key.public_key().to_bytes()
+}
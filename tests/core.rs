@@ -1,15 +1,36 @@
+mod common;
+
 #[cfg(test)]
 mod tests {
-    use std::u64;
+    use std::{fs, path::PathBuf};
 
-    use cryptowallet::wallet::core::Wallet;
-    use rand::RngCore;
-    use secp256k1::rand::{Rng, SeedableRng};
+    use bip32::{Prefix, PublicKey as _, Seed};
+    use cryptowallet::wallet::core::{
+        address_path, derive_child, gen_mnemonic, parse_mnemonic, Bip44Address, Bip44ChangeVal,
+        CoinType, NewAddressParams, PubKeyForm, Wallet,
+    };
+    use cryptowallet::wallet::encryption::{
+        EncryptedFile, INCORRECT_PASSWORD, TAMPERED_WALLET_FILE,
+    };
+    use cryptowallet::wallet::evm::{address_from_pubkey, to_checksum_address};
+    use cryptowallet::wallet::path::Purpose;
+    use cryptowallet::wallet::wallet_bitcoin::{
+        address_from_compressed_pub_key, p2shwpkh_address_from_compressed_pub_key,
+        p2tr_address_from_compressed_pub_key, p2wpkh_address_from_compressed_pub_key,
+        uncompressed_address_from_compressed_pub_key, x_only_pub_key,
+    };
     use secp256k1::{PublicKey, Secp256k1, SecretKey};
 
+    use crate::common::{temp_wallet_path, ZERO_ENTROPY_PHRASE_12};
+
+    // BIP39 test vector for all-zero entropy
+    const ZERO_ENTROPY_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
     #[test]
     fn gen_correct_pubkey() {
-        let pub_key = "adfjalkdsjfad".as_bytes();
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+        let pub_key = PublicKey::from_secret_key(&secp, &secret_key).serialize_uncompressed();
         // first byte as 0x04 indicates uncompressed key
         assert_eq!(pub_key[0], 0x04);
         // with the first byte and the key we expect 1 + 64 bytes for the entire public key
@@ -18,11 +39,11 @@ mod tests {
 
     #[test]
     fn gen_correct_private_key() {
-        let secret_key = "asl;kdjflakjdflajdsf".as_bytes();
+        let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
         // the 32 bytes are displayed as a 64 byte hexadecimal char string
-        assert_eq!(secret_key.len(), 64);
+        assert_eq!(secret_key.display_secret().to_string().len(), 64);
         // the actual length of the byte array is 32
-        assert_eq!(secret_key.len(), 32);
+        assert_eq!(secret_key.secret_bytes().len(), 32);
     }
 
     #[test]
@@ -32,7 +53,7 @@ mod tests {
         let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
 
         let public_key = PublicKey::from_secret_key(&secp, &secret_key);
-        let address = "addrss";
+        let address = address_from_pubkey(public_key.serialize_uncompressed());
         // test it with the representative hash
         assert_eq!(address.as_bytes().len(), 20);
         // address prevalidated using trusted tool
@@ -50,12 +71,716 @@ mod tests {
         let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
 
         let public_key = PublicKey::from_secret_key(&secp, &secret_key);
-        let wallet = Wallet::new();
+        let address = address_from_pubkey(public_key.serialize_uncompressed());
+
+        assert_eq!(
+            "0x89AEF553A06ab0C3173e79DE1Ce241A9ed3b992C",
+            to_checksum_address(&address)
+        )
+    }
+
+    #[test]
+    fn wallet_file_round_trips_with_password() {
+        let path = temp_wallet_path("round_trip");
+        let wallet = Wallet::new_at(&path, 24, "correct horse", None).unwrap();
+        let loaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        assert_eq!(wallet.mnemonic, loaded.mnemonic);
+        assert_eq!(
+            wallet.show_mnemonic().unwrap(),
+            loaded.show_mnemonic().unwrap()
+        );
+    }
+
+    #[test]
+    fn wallet_file_does_not_contain_plaintext_secrets() {
+        let path = temp_wallet_path("no_plaintext");
+        let wallet = Wallet::new_at(&path, 24, "correct horse", None).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+
+        assert!(!contents.contains("mnemonic"));
+        let first_word = wallet.show_mnemonic().unwrap();
+        let first_word = first_word.split(' ').next().unwrap();
+        assert!(!contents.contains(&format!("\"{}\"", first_word)));
+    }
+
+    #[test]
+    fn wrong_password_is_rejected() {
+        let path = temp_wallet_path("wrong_password");
+        Wallet::new_at(&path, 24, "correct horse", None).unwrap();
+        let err = Wallet::from_path(&path, "battery staple", None).unwrap_err();
+
+        assert_eq!(err.to_string(), INCORRECT_PASSWORD);
+    }
+
+    #[test]
+    fn unencrypted_wallet_files_are_encrypted_on_load() {
+        let path = temp_wallet_path("unencrypted");
+        fs::write(&path, include_str!("fixtures/baseline_wallet.json")).unwrap();
+        let wallet = Wallet::from_path(&path, "correct horse", None).unwrap();
+        assert_eq!(wallet.mnemonic, [0u8; 32]);
+        assert_eq!(
+            wallet.coins[&CoinType::Ethereum][&Purpose::Bip44].accounts[&0].name,
+            "main"
+        );
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let encrypted: EncryptedFile = serde_json::from_str(&contents).unwrap();
+        assert!(encrypted.open("correct horse").is_ok());
+        let err = Wallet::from_path(&path, "battery staple", None).unwrap_err();
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        assert_eq!(err.to_string(), INCORRECT_PASSWORD);
+        assert_eq!(reloaded.mnemonic, [0u8; 32]);
+        assert_eq!(
+            reloaded.coins[&CoinType::Bitcoin][&Purpose::Bip44].accounts[&0].name,
+            "savings"
+        );
+    }
+
+    #[test]
+    fn tampered_file_is_rejected() {
+        let path = temp_wallet_path("tampered");
+        Wallet::new_at(&path, 24, "correct horse", None).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let mut encrypted: EncryptedFile = serde_json::from_str(&contents).unwrap();
+        // flip the last nibble of the ciphertext
+        let last = encrypted.ciphertext.pop().unwrap();
+        encrypted
+            .ciphertext
+            .push(if last == '0' { '1' } else { '0' });
+        fs::write(&path, serde_json::to_string(&encrypted).unwrap()).unwrap();
+
+        let err = Wallet::from_path(&path, "correct horse", None).unwrap_err();
+
+        assert_eq!(err.to_string(), TAMPERED_WALLET_FILE);
+    }
+
+    #[test]
+    fn saving_replaces_the_file_in_one_step() {
+        let path = temp_wallet_path("atomic_save");
+        let temp_path = PathBuf::from(format!("{}.tmp", path.display()));
+        let wallet = Wallet::new_at(&path, 24, "correct horse", None).unwrap();
+        assert!(!temp_path.exists());
+
+        // a save that can't be written leaves the last one in place
+        fs::create_dir(&temp_path).unwrap();
+        assert!(Wallet::new_at(&path, 24, "battery staple", None).is_err());
+        fs::remove_dir(&temp_path).unwrap();
+
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+        assert_eq!(reloaded.mnemonic, wallet.mnemonic);
+    }
+
+    #[test]
+    fn restores_wallet_from_mnemonic() {
+        let path = temp_wallet_path("restore");
+        let wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE, "correct horse", None).unwrap();
+        let loaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        assert_eq!(wallet.mnemonic, vec![0u8; 32]);
+        assert_eq!(loaded.show_mnemonic().unwrap(), ZERO_ENTROPY_PHRASE);
+    }
+
+    #[test]
+    fn mnemonic_ignores_case_and_extra_whitespace() {
+        let messy = format!(
+            "  {}  ",
+            ZERO_ENTROPY_PHRASE.to_uppercase().replace(' ', "   ")
+        );
+        assert_eq!(parse_mnemonic(&messy).unwrap(), vec![0u8; 32]);
+    }
+
+    #[test]
+    fn mnemonic_with_unknown_word_is_rejected() {
+        let phrase = ZERO_ENTROPY_PHRASE.replacen("abandon", "abandonn", 1);
+        let err = parse_mnemonic(&phrase).unwrap_err();
+        assert!(err.to_string().contains("abandonn"));
+    }
+
+    #[test]
+    fn mnemonic_with_bad_checksum_is_rejected() {
+        let phrase = ZERO_ENTROPY_PHRASE.replace("art", "abandon");
+        assert!(parse_mnemonic(&phrase).is_err());
+    }
+
+    #[test]
+    fn gen_mnemonic_supports_all_bip39_lengths() {
+        for word_count in [12, 15, 18, 21, 24] {
+            let mnemonic = gen_mnemonic(word_count).unwrap();
+            assert_eq!(mnemonic.word_count(), word_count);
+            assert_eq!(mnemonic.to_entropy().len(), word_count / 3 * 4);
+        }
+        assert!(gen_mnemonic(13).is_err());
+        assert!(gen_mnemonic(25).is_err());
+    }
+
+    #[test]
+    fn twelve_word_wallet_round_trips() {
+        let path = temp_wallet_path("twelve_words");
+        let wallet = Wallet::new_at(&path, 12, "correct horse", None).unwrap();
+        let loaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        assert_eq!(wallet.mnemonic.len(), 16);
+        assert_eq!(loaded.word_count, 12);
+        assert_eq!(loaded.show_mnemonic().unwrap().split(' ').count(), 12);
+    }
+
+    #[test]
+    fn restores_twelve_word_mnemonic() {
+        let path = temp_wallet_path("restore_twelve_words");
+        let wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+
+        assert_eq!(wallet.mnemonic, vec![0u8; 16]);
+        assert_eq!(wallet.word_count, 12);
+        assert_eq!(wallet.show_mnemonic().unwrap(), ZERO_ENTROPY_PHRASE_12);
+    }
+
+    #[test]
+    fn master_fingerprint_depends_on_passphrase() {
+        let path = temp_wallet_path("fingerprint");
+        let plain =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        let hidden = Wallet::from_mnemonic_at(
+            &path,
+            ZERO_ENTROPY_PHRASE_12,
+            "correct horse",
+            Some("TREZOR"),
+        )
+        .unwrap();
+
+        // well known fingerprint of the "abandon ... about" test mnemonic
+        assert_eq!(plain.master_fingerprint, "73c5da0a");
+        assert_ne!(hidden.master_fingerprint, plain.master_fingerprint);
+    }
+
+    #[test]
+    fn passphrase_is_never_written_to_disk() {
+        let path = temp_wallet_path("passphrase_not_saved");
+        Wallet::new_at(&path, 24, "correct horse", Some("very hidden words")).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+
+        let encrypted: EncryptedFile = serde_json::from_str(&contents).unwrap();
+        let (plaintext, _) = encrypted.open("correct horse").unwrap();
+        assert!(!String::from_utf8(plaintext)
+            .unwrap()
+            .contains("very hidden words"));
+    }
+
+    #[test]
+    fn passphrases_open_separate_hidden_wallets() {
+        let path = temp_wallet_path("hidden_wallets");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        wallet
+            .new_account(CoinType::Bitcoin, Purpose::Bip44, "visible")
+            .unwrap();
+        wallet.save_to_file().unwrap();
+
+        let mut hidden = Wallet::from_path(&path, "correct horse", Some("hidden")).unwrap();
+        assert!(hidden.coins.is_empty());
+        hidden
+            .new_account(CoinType::Bitcoin, Purpose::Bip44, "secret")
+            .unwrap();
+        hidden.save_to_file().unwrap();
+
+        let wallet = Wallet::from_path(&path, "correct horse", None).unwrap();
+        let hidden = Wallet::from_path(&path, "correct horse", Some("hidden")).unwrap();
+        // a mistyped passphrase opens an empty wallet without disturbing the others
+        Wallet::from_path(&path, "correct horse", Some("hiden"))
+            .unwrap()
+            .save_to_file()
+            .unwrap();
+        let after_typo = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        let name = |wallet: &Wallet| {
+            wallet.coins[&CoinType::Bitcoin][&Purpose::Bip44].accounts[&0]
+                .name
+                .clone()
+        };
+        assert_eq!(name(&wallet), "visible");
+        assert_eq!(name(&hidden), "secret");
+        assert_eq!(after_typo.hidden_wallets.len(), 1);
+        assert_eq!(name(&after_typo), "visible");
+    }
+
+    #[test]
+    fn hidden_wallets_are_sealed_under_their_passphrase() {
+        let path = temp_wallet_path("hidden_sealed");
+        Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        let mut hidden = Wallet::from_path(&path, "correct horse", Some("hidden")).unwrap();
+        hidden
+            .new_account(CoinType::Ethereum, Purpose::Bip44, "secret savings")
+            .unwrap();
+        hidden.save_to_file().unwrap();
+        let hidden_fingerprint = hidden.master_fingerprint.clone();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let encrypted: EncryptedFile = serde_json::from_str(&contents).unwrap();
+        let (plaintext, _) = encrypted.open("correct horse").unwrap();
+        let plaintext = String::from_utf8(plaintext).unwrap();
+        for secret in ["secret savings", hidden_fingerprint.as_str()] {
+            assert!(!plaintext.contains(secret));
+        }
+
+        let plain = Wallet::from_path(&path, "correct horse", None).unwrap();
+        assert!(plain.coins.is_empty());
+        assert_eq!(plain.master_fingerprint, "73c5da0a");
+        assert_eq!(plain.hidden_wallets.len(), 1);
+        let reopened = Wallet::from_path(&path, "correct horse", Some("hidden")).unwrap();
+        assert!(reopened.hidden_wallets.is_empty());
+        assert_eq!(
+            reopened.coins[&CoinType::Ethereum][&Purpose::Bip44].accounts[&0].name,
+            "secret savings"
+        );
+    }
+
+    #[test]
+    fn address_path_hardens_purpose_coin_and_account() {
+        assert_eq!(
+            address_path(
+                Purpose::Bip44,
+                CoinType::Ethereum,
+                3,
+                &Bip44ChangeVal::INTERNAL,
+                7
+            )
+            .unwrap()
+            .to_string(),
+            "m/44'/60'/3'/1/7"
+        );
+    }
+
+    #[test]
+    fn derive_child_enforces_bip44_hardening() {
+        let seed = Seed::new([0x42; 64]);
+        let derive = |path: &str| derive_child(&seed, &path.parse().unwrap());
+        assert!(derive("m/44'/60'/0'/0/0").is_ok());
+        // old layout with an unhardened coin type and change before account
+        assert!(derive("m/44'/60/0/0/0").is_err());
+        // unhardened account
+        assert!(derive("m/44'/60'/0/0/0").is_err());
+        // hardened change and address index
+        assert!(derive("m/44'/60'/0'/0'/0").is_err());
+        assert!(derive("m/44'/60'/0'/0/0'").is_err());
+        // the same rules apply to the segwit and taproot purposes
+        assert!(derive("m/84'/0'/0'/1/3").is_ok());
+        assert!(derive("m/84'/0/0'/1/3").is_err());
+    }
+
+    #[test]
+    fn derive_child_accepts_paths_outside_bip44() {
+        let seed = Seed::new([0x42; 64]);
+        // BIP85 style
+        assert!(derive_child(&seed, &"m/83696968'/39'/0'/12'/0'".parse().unwrap()).is_ok());
+        // arbitrary depth and unhardened levels
+        assert!(derive_child(&seed, &"m/0/1/2/3/4/5/6".parse().unwrap()).is_ok());
+        assert!(derive_child(&seed, &"m".parse().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn old_path_layout_is_migrated_on_load() {
+        let path = temp_wallet_path("legacy_paths");
+        fs::write(&path, include_str!("fixtures/baseline_wallet.json")).unwrap();
+        let migrated = Wallet::from_path(&path, "correct horse", None).unwrap();
+        // migration is saved straight away
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        // (coin, index, address at the BIP44 path, old path, old address) for ZERO_ENTROPY_PHRASE
+        let expected = [
+            (
+                CoinType::Bitcoin,
+                0,
+                "1KBdbBJRVYffWHWWZ1moECfdVBSEnDpLHi",
+                "m/44'/0/0/0/0",
+                "19AsQVbwyEVz1WuMbWiwhHGHbokA59yQUr",
+            ),
+            (
+                CoinType::Ethereum,
+                0,
+                "0xF278cF59F82eDcf871d630F28EcC8056f25C1cdb",
+                "m/44'/60/0/0/0",
+                "0x815da8a4b67584F104F94A5E4Bb4413cF71C56CC",
+            ),
+            (
+                CoinType::Ethereum,
+                1,
+                "0xf785bD075874b8423D3583728a981399f31e95aA",
+                "m/44'/60/0/0/1",
+                "0x349BF2B92B8CFA114F52150e450C52c8d9523f12",
+            ),
+        ];
+        for wallet in [migrated, reloaded] {
+            for (coin, index, new_address, old_path, old_address) in expected {
+                let address = &wallet.coins[&coin][&Purpose::Bip44].accounts[&0].changes
+                    [&Bip44ChangeVal::RECEIVING]
+                    .addresses[&index];
+                assert_eq!(
+                    address.path.to_string(),
+                    format!("m/44'/{}'/0'/0/{}", coin, index)
+                );
+                assert_eq!(address.address_checksummed, new_address);
+                let legacy = address.legacy.as_ref().unwrap();
+                assert_eq!(legacy.path, old_path);
+                assert_eq!(legacy.address, old_address);
+            }
+        }
+    }
+
+    #[test]
+    fn bip84_accounts_are_numbered_separately() {
+        let path = temp_wallet_path("bip84_accounts");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        assert_eq!(
+            wallet
+                .new_account(CoinType::Bitcoin, Purpose::Bip44, "legacy")
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            wallet
+                .new_account(CoinType::Bitcoin, Purpose::Bip84, "segwit")
+                .unwrap(),
+            0
+        );
+        assert!(wallet
+            .new_account(CoinType::Ethereum, Purpose::Bip84, "nope")
+            .is_err());
+        wallet.save_to_file().unwrap();
+
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        let segwit = &reloaded.coins[&CoinType::Bitcoin][&Purpose::Bip84];
+        assert_eq!(segwit.path, "m/84'/0'");
+        assert_eq!(segwit.accounts[&0].name, "segwit");
+    }
+
+    #[test]
+    fn bip84_addresses_match_reference_vectors() {
+        let mnemonic = bip39::Mnemonic::parse(ZERO_ENTROPY_PHRASE_12).unwrap();
+        let seed = Seed::new(mnemonic.to_seed(""));
+        let pub_key = |path: &str| {
+            let key = derive_child(&seed, &path.parse().unwrap()).unwrap();
+            key.pub_key.public_key().to_bytes()
+        };
+
+        // BIP84 test vectors
+        let address = p2wpkh_address_from_compressed_pub_key(
+            pub_key("m/84'/0'/0'/0/0"),
+            bitcoin::Network::Bitcoin,
+        )
+        .unwrap();
+        assert_eq!(
+            address.to_string(),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        let change = p2wpkh_address_from_compressed_pub_key(
+            pub_key("m/84'/0'/0'/1/0"),
+            bitcoin::Network::Bitcoin,
+        )
+        .unwrap();
+        assert_eq!(
+            change.to_string(),
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+        );
 
-        println!("{}", "address!");
+        let testnet = p2wpkh_address_from_compressed_pub_key(
+            pub_key("m/84'/1'/0'/0/0"),
+            bitcoin::Network::Testnet,
+        )
+        .unwrap();
+        assert!(testnet.to_string().starts_with("tb1q"));
+    }
+
+    #[test]
+    fn wallets_from_before_purposes_still_load() {
+        let path = temp_wallet_path("pre_purpose_layout");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        wallet
+            .new_account(CoinType::Ethereum, Purpose::Bip44, "main")
+            .unwrap();
+        wallet.save_to_file().unwrap();
+
+        // rewrite the file with a single BIP44 tree per coin, like older versions did
+        let contents = fs::read_to_string(&path).unwrap();
+        let encrypted: EncryptedFile = serde_json::from_str(&contents).unwrap();
+        let (plaintext, key) = encrypted.open("correct horse").unwrap();
+        let mut json: serde_json::Value = serde_json::from_slice(&plaintext).unwrap();
+        let eth = json["coins"]["Ethereum"]["Bip44"].take();
+        json["coins"]["Ethereum"] = eth;
+        let resealed = key.seal(json.to_string().as_bytes()).unwrap();
+        fs::write(&path, serde_json::to_string(&resealed).unwrap()).unwrap();
+
+        let loaded = Wallet::from_path(&path, "correct horse", None).unwrap();
 
-        assert_eq!("0x89AEF553A06ab0C3173e79DE1Ce241A9ed3b992C", "address!")
+        assert_eq!(
+            loaded.coins[&CoinType::Ethereum][&Purpose::Bip44].accounts[&0].name,
+            "main"
+        );
     }
 
-    // we need to check what properties there are and then test for them
+    #[test]
+    fn bip49_addresses_match_reference_vectors() {
+        let mnemonic = bip39::Mnemonic::parse(ZERO_ENTROPY_PHRASE_12).unwrap();
+        let seed = Seed::new(mnemonic.to_seed(""));
+        let pub_key = |path: &str| {
+            let key = derive_child(&seed, &path.parse().unwrap()).unwrap();
+            key.pub_key.public_key().to_bytes()
+        };
+
+        let address = p2shwpkh_address_from_compressed_pub_key(
+            pub_key("m/49'/0'/0'/0/0"),
+            bitcoin::Network::Bitcoin,
+        )
+        .unwrap();
+        assert_eq!(address.to_string(), "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf");
+
+        // BIP49 test vector
+        let testnet = p2shwpkh_address_from_compressed_pub_key(
+            pub_key("m/49'/1'/0'/0/0"),
+            bitcoin::Network::Testnet,
+        )
+        .unwrap();
+        assert_eq!(testnet.to_string(), "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2");
+    }
+
+    #[test]
+    fn bip49_accounts_are_bitcoin_only() {
+        let path = temp_wallet_path("bip49_accounts");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+
+        assert!(wallet
+            .new_account(CoinType::BitcoinTestnet, Purpose::Bip49, "nested")
+            .is_ok());
+        assert_eq!(
+            wallet.coins[&CoinType::BitcoinTestnet][&Purpose::Bip49].path,
+            "m/49'/1'"
+        );
+        assert!(wallet
+            .new_account(CoinType::Ethereum, Purpose::Bip49, "nope")
+            .is_err());
+    }
+
+    #[test]
+    fn bip86_addresses_match_reference_vectors() {
+        let mnemonic = bip39::Mnemonic::parse(ZERO_ENTROPY_PHRASE_12).unwrap();
+        let seed = Seed::new(mnemonic.to_seed(""));
+        let pub_key = |path: &str| {
+            let key = derive_child(&seed, &path.parse().unwrap()).unwrap();
+            key.pub_key.public_key().to_bytes()
+        };
+
+        // BIP86 test vectors
+        let first = pub_key("m/86'/0'/0'/0/0");
+        assert_eq!(
+            hex::encode(x_only_pub_key(first).unwrap()),
+            "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115"
+        );
+        let address =
+            p2tr_address_from_compressed_pub_key(first, bitcoin::Network::Bitcoin).unwrap();
+        assert_eq!(
+            address.to_string(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+        let second = p2tr_address_from_compressed_pub_key(
+            pub_key("m/86'/0'/0'/0/1"),
+            bitcoin::Network::Bitcoin,
+        )
+        .unwrap();
+        assert_eq!(
+            second.to_string(),
+            "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh"
+        );
+        let change = p2tr_address_from_compressed_pub_key(
+            pub_key("m/86'/0'/0'/1/0"),
+            bitcoin::Network::Bitcoin,
+        )
+        .unwrap();
+        assert_eq!(
+            change.to_string(),
+            "bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7"
+        );
+    }
+
+    #[test]
+    fn legacy_addresses_hash_the_compressed_key() {
+        let mnemonic = bip39::Mnemonic::parse(ZERO_ENTROPY_PHRASE_12).unwrap();
+        let seed = Seed::new(mnemonic.to_seed(""));
+        let key = derive_child(&seed, &"m/44'/0'/0'/0/0".parse().unwrap()).unwrap();
+        let pub_key = key.pub_key.public_key().to_bytes();
+
+        // same as every other BIP44 wallet for the "abandon ... about" mnemonic
+        let address = address_from_compressed_pub_key(pub_key, bitcoin::Network::Bitcoin).unwrap();
+        assert_eq!(address.to_string(), "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA");
+    }
+
+    #[test]
+    fn uncompressed_addresses_can_still_be_recovered() {
+        let path = temp_wallet_path("uncompressed_recovery");
+        let wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+
+        let recovered = wallet
+            .recover_uncompressed_address(CoinType::Bitcoin, 0, &Bip44ChangeVal::RECEIVING, 0)
+            .unwrap();
+        assert_eq!(recovered.key_form, PubKeyForm::Uncompressed);
+        assert_eq!(recovered.path.to_string(), "m/44'/0'/0'/0/0");
+        assert!(recovered.address.starts_with('1'));
+        assert_ne!(recovered.address, "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA");
+    }
+
+    #[test]
+    fn baseline_addresses_default_to_the_uncompressed_key() {
+        let fixture: serde_json::Value =
+            serde_json::from_str(include_str!("fixtures/baseline_wallet.json")).unwrap();
+        let first_address = |coin: &str| -> Bip44Address {
+            let chain = &fixture["coins"][coin]["accounts"]["0"]["changes"]["RECEIVING"];
+            serde_json::from_value(chain["addresses"]["0"].clone()).unwrap()
+        };
+
+        let bitcoin = first_address("Bitcoin");
+        assert_eq!(bitcoin.key_form, PubKeyForm::Uncompressed);
+        let pub_key: [u8; 33] = bitcoin.pub_key.as_slice().try_into().unwrap();
+        let address =
+            uncompressed_address_from_compressed_pub_key(pub_key, bitcoin::Network::Bitcoin)
+                .unwrap();
+        assert_eq!(address.to_string(), bitcoin.address);
+
+        let ethereum = first_address("Ethereum");
+        assert_eq!(ethereum.key_form, PubKeyForm::Uncompressed);
+        let pub_key = PublicKey::from_slice(&ethereum.pub_key).unwrap();
+        let address = address_from_pubkey(pub_key.serialize_uncompressed());
+        assert_eq!(to_checksum_address(&address), ethereum.address_checksummed);
+    }
+
+    #[test]
+    fn watch_only_wallets_hold_no_secrets() {
+        let mnemonic = bip39::Mnemonic::parse(ZERO_ENTROPY_PHRASE_12).unwrap();
+        let seed = Seed::new(mnemonic.to_seed(""));
+        let account = derive_child(&seed, &"m/84'/0'/2'".parse().unwrap()).unwrap();
+        let xpub = account.pub_key.to_string(Prefix::ZPUB);
+
+        let path = temp_wallet_path("watch_only");
+        let mut wallet = Wallet::watch_only_at(
+            &path,
+            &xpub,
+            CoinType::Bitcoin,
+            Purpose::Bip84,
+            "payments",
+            "correct horse",
+        )
+        .unwrap();
+        assert!(wallet.watch_only);
+        assert!(wallet.mnemonic.is_empty());
+        assert!(wallet.show_mnemonic().is_err());
+        assert!(wallet
+            .new_account(CoinType::Bitcoin, Purpose::Bip84, "more")
+            .is_err());
+        assert!(wallet
+            .recover_uncompressed_address(CoinType::Bitcoin, 2, &Bip44ChangeVal::RECEIVING, 0)
+            .is_err());
+
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+
+        assert!(!contents.contains(&xpub));
+        let account = &reloaded.coins[&CoinType::Bitcoin][&Purpose::Bip84].accounts[&2];
+        assert_eq!(account.name, "payments");
+        assert_eq!(account.xpub.as_deref(), Some(xpub.as_str()));
+    }
+
+    #[test]
+    fn watch_only_wallets_need_an_account_xpub() {
+        let seed = Seed::new([0x42; 64]);
+        let account = derive_child(&seed, &"m/44'/0'/0'".parse().unwrap()).unwrap();
+        let address = derive_child(&seed, &"m/44'/0'/0'/0/0".parse().unwrap()).unwrap();
+        let path = temp_wallet_path("watch_only_rejected");
+        let watch_only = |key: &str| {
+            Wallet::watch_only_at(
+                &path,
+                key,
+                CoinType::Bitcoin,
+                Purpose::Bip44,
+                "payments",
+                "correct horse",
+            )
+        };
+
+        // never accept the private key
+        let xprv = account.priv_key.to_string(Prefix::XPRV);
+        assert!(watch_only(&xprv).is_err());
+        assert!(watch_only(&address.pub_key.to_string(Prefix::XPUB)).is_err());
+        assert!(watch_only("xpub-not-really").is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn address_batches_are_saved_once_generated() {
+        let path = temp_wallet_path("address_batch");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        wallet
+            .new_account(CoinType::Bitcoin, Purpose::Bip84, "deposits")
+            .unwrap();
+        let prms = NewAddressParams::new(CoinType::Bitcoin, 0).purpose(Purpose::Bip84);
+        wallet.new_address(prms).unwrap();
+
+        let batch = wallet.new_addresses(prms, 3).unwrap();
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        let paths: Vec<String> = batch.iter().map(|a| a.path.to_string()).collect();
+        assert_eq!(
+            paths,
+            ["m/84'/0'/0'/0/1", "m/84'/0'/0'/0/2", "m/84'/0'/0'/0/3"]
+        );
+        let receiving = &reloaded.coins[&CoinType::Bitcoin][&Purpose::Bip84].accounts[&0].changes
+            [&Bip44ChangeVal::RECEIVING];
+        assert_eq!(receiving.next_address_index, 4);
+        assert_eq!(
+            receiving.addresses[&0].address,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(receiving.addresses[&3].address, batch[2].address);
+
+        let change = prms.change(Bip44ChangeVal::INTERNAL);
+        assert_eq!(
+            wallet.new_addresses(change, 1).unwrap()[0].address,
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+        );
+        assert!(wallet
+            .new_addresses(NewAddressParams::new(CoinType::Bitcoin, 1), 5)
+            .is_err());
+    }
+
+    #[test]
+    fn watch_only_wallets_derive_addresses_from_their_xpub() {
+        let mnemonic = bip39::Mnemonic::parse(ZERO_ENTROPY_PHRASE_12).unwrap();
+        let seed = Seed::new(mnemonic.to_seed(""));
+        let account = derive_child(&seed, &"m/84'/0'/0'".parse().unwrap()).unwrap();
+
+        let path = temp_wallet_path("watch_only_addresses");
+        let mut wallet = Wallet::watch_only_at(
+            &path,
+            &account.pub_key.to_string(Prefix::ZPUB),
+            CoinType::Bitcoin,
+            Purpose::Bip84,
+            "payments",
+            "correct horse",
+        )
+        .unwrap();
+        let prms = NewAddressParams::new(CoinType::Bitcoin, 0).purpose(Purpose::Bip84);
+        let batch = wallet.new_addresses(prms, 2).unwrap();
+
+        assert_eq!(
+            batch[0].address,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(batch[1].path.to_string(), "m/84'/0'/0'/0/1");
+    }
 }
@@ -0,0 +1,59 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use cryptowallet::wallet::coins::{adapter, coins_for_address};
+    use cryptowallet::wallet::core::{CoinType, PubKeyForm};
+    use cryptowallet::wallet::cosmos::address_for_hrp;
+    use cryptowallet::wallet::path::Purpose;
+
+    use crate::common::pub_key;
+
+    // Keplr and gaiad for ZERO_ENTROPY_PHRASE_12 at m/44'/118'/0'/0/0
+    const FIRST_ADDRESS: &str = "cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4";
+
+    fn address(coin: CoinType, path: &str) -> String {
+        adapter(coin)
+            .encode_address(&pub_key(path), Some(Purpose::Bip44), PubKeyForm::Compressed)
+            .unwrap()
+            .address
+    }
+
+    #[test]
+    fn cosmos_chains_share_coin_type_118() {
+        assert_eq!(
+            address(CoinType::Cosmos, "m/44'/118'/0'/0/0"),
+            FIRST_ADDRESS
+        );
+        let osmosis = address(CoinType::Osmosis, "m/44'/118'/0'/0/0");
+        assert!(osmosis.starts_with("osmo1"));
+        assert_eq!(address_for_hrp(FIRST_ADDRESS, "osmo").unwrap(), osmosis);
+        assert_eq!(
+            address_for_hrp(&osmosis, "juno").unwrap(),
+            address(CoinType::Juno, "m/44'/118'/0'/0/0")
+        );
+        for coin in [CoinType::Cosmos, CoinType::Osmosis, CoinType::Juno] {
+            assert_eq!(coin.slip44(), 118);
+            assert!(!coin.supports_purpose(Purpose::Bip84));
+        }
+    }
+
+    #[test]
+    fn cosmos_addresses_check_their_hrp() {
+        let cosmos = adapter(CoinType::Cosmos);
+        assert_eq!(
+            cosmos
+                .checksum_address(&FIRST_ADDRESS.to_uppercase())
+                .unwrap(),
+            FIRST_ADDRESS
+        );
+        let osmosis = address_for_hrp(FIRST_ADDRESS, "osmo").unwrap();
+        assert!(!cosmos.validate_address(&osmosis));
+        assert!(adapter(CoinType::Osmosis).validate_address(&osmosis));
+        assert!(!cosmos.validate_address("cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal5"));
+        // bitcoin segwit addresses are bech32 too, but not 20 bytes behind a cosmos HRP
+        assert!(!cosmos.validate_address("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"));
+        assert_eq!(coins_for_address(FIRST_ADDRESS), [CoinType::Cosmos]);
+        assert_eq!(coins_for_address(&osmosis), [CoinType::Osmosis]);
+    }
+}
@@ -0,0 +1,153 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use bip32::{Prefix, PublicKey as _, Seed};
+    use cryptowallet::wallet::core::{derive_child, Bip44ChangeVal, CoinType, Wallet};
+    use cryptowallet::wallet::discovery::{MockAddressHistory, DEFAULT_GAP_LIMIT};
+    use cryptowallet::wallet::path::Purpose;
+    use cryptowallet::wallet::wallet_bitcoin::p2wpkh_address_from_compressed_pub_key;
+
+    use crate::common::{temp_wallet_path, ZERO_ENTROPY_PHRASE_12};
+
+    fn seed() -> Seed {
+        let mnemonic = bip39::Mnemonic::parse(ZERO_ENTROPY_PHRASE_12).unwrap();
+        Seed::new(mnemonic.to_seed(""))
+    }
+
+    /// BIP84 address at `m/84'/0'/account'/change/index`
+    fn segwit_address(account: u32, change: u32, index: u32) -> String {
+        let path = format!("m/84'/0'/{}'/{}/{}", account, change, index);
+        let key = derive_child(&seed(), &path.parse().unwrap()).unwrap();
+        p2wpkh_address_from_compressed_pub_key(
+            key.pub_key.public_key().to_bytes(),
+            bitcoin::Network::Bitcoin,
+        )
+        .unwrap()
+        .to_string()
+    }
+
+    fn restored_wallet(name: &str) -> Wallet {
+        let path = temp_wallet_path(name);
+        Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap()
+    }
+
+    #[test]
+    fn discovers_used_accounts_and_chains() {
+        let mut history = MockAddressHistory::new();
+        for (account, change, index) in [(0, 0, 0), (0, 0, 5), (0, 1, 2), (1, 0, 3)] {
+            history.mark_used(CoinType::Bitcoin, &segwit_address(account, change, index));
+        }
+        // used by account 3, but account 2 was never used so discovery stops before it
+        history.mark_used(CoinType::Bitcoin, &segwit_address(3, 0, 0));
+
+        let mut wallet = restored_wallet("discovery");
+        let used = wallet
+            .discover_accounts(
+                CoinType::Bitcoin,
+                Purpose::Bip84,
+                &history,
+                DEFAULT_GAP_LIMIT,
+            )
+            .unwrap();
+        assert_eq!(used, vec![0, 1]);
+
+        let accounts = &wallet.coins[&CoinType::Bitcoin][&Purpose::Bip84];
+        assert_eq!(accounts.next_index, 2);
+        let account = &accounts.accounts[&0];
+        let receiving = &account.changes[&Bip44ChangeVal::RECEIVING];
+        assert_eq!(receiving.next_address_index, 6);
+        assert_eq!(receiving.addresses.len(), 6);
+        assert_eq!(
+            receiving.addresses[&0].address,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(
+            account.changes[&Bip44ChangeVal::INTERNAL].next_address_index,
+            3
+        );
+        let account = &accounts.accounts[&1];
+        assert_eq!(
+            account.changes[&Bip44ChangeVal::RECEIVING].next_address_index,
+            4
+        );
+        assert_eq!(
+            account.changes[&Bip44ChangeVal::INTERNAL].next_address_index,
+            0
+        );
+    }
+
+    #[test]
+    fn stops_at_the_gap_limit() {
+        let mut history = MockAddressHistory::new();
+        history.mark_used(CoinType::Bitcoin, &segwit_address(0, 0, 0));
+        // 5 unused addresses in between
+        history.mark_used(CoinType::Bitcoin, &segwit_address(0, 0, 6));
+
+        let mut wallet = restored_wallet("discovery_gap");
+        wallet
+            .discover_accounts(CoinType::Bitcoin, Purpose::Bip84, &history, 5)
+            .unwrap();
+        let receiving = &wallet.coins[&CoinType::Bitcoin][&Purpose::Bip84].accounts[&0].changes
+            [&Bip44ChangeVal::RECEIVING];
+        assert_eq!(receiving.next_address_index, 1);
+
+        wallet
+            .discover_accounts(CoinType::Bitcoin, Purpose::Bip84, &history, 6)
+            .unwrap();
+        let receiving = &wallet.coins[&CoinType::Bitcoin][&Purpose::Bip84].accounts[&0].changes
+            [&Bip44ChangeVal::RECEIVING];
+        assert_eq!(receiving.next_address_index, 7);
+
+        assert!(wallet
+            .discover_accounts(CoinType::Bitcoin, Purpose::Bip84, &history, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn unused_wallets_stay_empty() {
+        let mut wallet = restored_wallet("discovery_unused");
+        let used = wallet
+            .discover_accounts(
+                CoinType::Bitcoin,
+                Purpose::Bip84,
+                &MockAddressHistory::new(),
+                DEFAULT_GAP_LIMIT,
+            )
+            .unwrap();
+        assert!(used.is_empty());
+        assert!(wallet.coins.is_empty());
+    }
+
+    #[test]
+    fn watch_only_wallets_scan_their_xpub_account() {
+        let mut history = MockAddressHistory::new();
+        history.mark_used(CoinType::Bitcoin, &segwit_address(2, 0, 4));
+
+        let account = derive_child(&seed(), &"m/84'/0'/2'".parse().unwrap()).unwrap();
+        let path = temp_wallet_path("discovery_watch_only");
+        let mut wallet = Wallet::watch_only_at(
+            &path,
+            &account.pub_key.to_string(Prefix::ZPUB),
+            CoinType::Bitcoin,
+            Purpose::Bip84,
+            "payments",
+            "correct horse",
+        )
+        .unwrap();
+
+        let used = wallet
+            .discover_accounts(
+                CoinType::Bitcoin,
+                Purpose::Bip84,
+                &history,
+                DEFAULT_GAP_LIMIT,
+            )
+            .unwrap();
+        assert_eq!(used, vec![2]);
+        let receiving = &wallet.coins[&CoinType::Bitcoin][&Purpose::Bip84].accounts[&2].changes
+            [&Bip44ChangeVal::RECEIVING];
+        assert_eq!(receiving.next_address_index, 5);
+        assert_eq!(receiving.addresses[&4].address, segwit_address(2, 0, 4));
+    }
+}
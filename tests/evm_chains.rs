@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use cryptowallet::wallet::evm_chains::{
+        ChainRegistry, EvmChain, ARBITRUM_ONE, BASE, ETHEREUM_MAINNET, POLYGON, SEPOLIA,
+    };
+    use web3::types::{H256, U256};
+
+    // answers every JSON-RPC request with `result`, returns the endpoint
+    fn json_rpc_server(result: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let response = format!(r#"{{"jsonrpc":"2.0","id":0,"result":"{}"}}"#, result);
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .unwrap();
+            }
+        });
+        endpoint
+    }
+
+    // nothing listens on the port of a listener that was dropped
+    fn unreachable_endpoint() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    fn anvil(rpc_endpoints: Vec<String>) -> EvmChain {
+        EvmChain {
+            chain_id: 31337,
+            name: "Anvil".to_string(),
+            native_symbol: "ETH".to_string(),
+            decimals: 18,
+            rpc_endpoints,
+            explorer_url: None,
+            testnet: true,
+        }
+    }
+
+    #[test]
+    fn builtin_chains_are_registered_by_id() {
+        let registry = ChainRegistry::default();
+        let ids: Vec<u64> = registry.chains().map(|chain| chain.chain_id).collect();
+        for chain_id in [ETHEREUM_MAINNET, SEPOLIA, POLYGON, ARBITRUM_ONE, BASE] {
+            assert!(ids.contains(&chain_id));
+        }
+        let mut sorted = ids.clone();
+        sorted.sort();
+        assert_eq!(ids, sorted);
+
+        let sepolia = registry.get(SEPOLIA).unwrap();
+        assert_eq!(sepolia.name, "Sepolia");
+        assert!(sepolia.testnet);
+        assert_eq!(registry.by_name("polygon").unwrap().native_symbol, "POL");
+        assert!(registry.get(5).is_none());
+        for chain in registry.chains() {
+            assert_eq!(chain.decimals, 18);
+            assert!(!chain.rpc_endpoints.is_empty());
+        }
+    }
+
+    #[test]
+    fn custom_chains_replace_builtin_ones() {
+        let mut registry = ChainRegistry::default();
+        let devnet = EvmChain {
+            chain_id: 31337,
+            name: "Anvil".to_string(),
+            native_symbol: "ETH".to_string(),
+            decimals: 18,
+            rpc_endpoints: vec!["http://127.0.0.1:8545".to_string()],
+            explorer_url: None,
+            testnet: true,
+        };
+        assert!(registry.insert(devnet.clone()).is_none());
+        assert_eq!(registry.get(31337), Some(&devnet));
+        assert_eq!(devnet.explorer_address_url("0x00"), None);
+
+        let base = EvmChain {
+            rpc_endpoints: vec!["wss://base.example".to_string()],
+            ..registry.get(BASE).unwrap().clone()
+        };
+        let replaced = registry.insert(base).unwrap();
+        assert_eq!(replaced.rpc_endpoints, ["https://mainnet.base.org"]);
+        assert_eq!(
+            registry.get(BASE).unwrap().rpc_endpoints,
+            ["wss://base.example"]
+        );
+    }
+
+    #[test]
+    fn amounts_and_explorer_links_use_the_chain() {
+        let registry = ChainRegistry::default();
+        let ethereum = registry.get(ETHEREUM_MAINNET).unwrap();
+        let wei = U256::exp10(18);
+        assert_eq!(ethereum.format_amount(wei * 3 / 2), "1.5 ETH");
+        assert_eq!(ethereum.format_amount(wei * 2), "2 ETH");
+        assert_eq!(
+            ethereum.format_amount(U256::one()),
+            "0.000000000000000001 ETH"
+        );
+        assert_eq!(ethereum.format_amount(U256::zero()), "0 ETH");
+
+        let polygon = registry.get(POLYGON).unwrap();
+        assert_eq!(
+            polygon
+                .explorer_address_url("0x9858EfFD232B4033E47d90003D41EC34EcaEda94")
+                .unwrap(),
+            "https://polygonscan.com/address/0x9858EfFD232B4033E47d90003D41EC34EcaEda94"
+        );
+        assert_eq!(
+            polygon.explorer_tx_url(&H256::zero()).unwrap(),
+            format!("https://polygonscan.com/tx/0x{}", "0".repeat(64))
+        );
+    }
+
+    #[tokio::test]
+    async fn connect_falls_back_to_the_next_endpoint() {
+        // 0x7a69 is 31337, 0x1 is mainnet
+        let anvil_endpoint = json_rpc_server("0x7a69");
+        let mainnet_endpoint = json_rpc_server("0x1");
+        let chain = anvil(vec![
+            unreachable_endpoint(),
+            mainnet_endpoint.clone(),
+            anvil_endpoint,
+        ]);
+        let web3 = chain.connect().await.unwrap();
+        assert_eq!(web3.eth().chain_id().await.unwrap(), U256::from(31337));
+
+        let error = anvil(vec![unreachable_endpoint(), mainnet_endpoint])
+            .connect()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "RPC endpoint is connected to a different chain"
+        );
+        assert!(anvil(Vec::new()).connect().await.is_err());
+    }
+}
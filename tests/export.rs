@@ -0,0 +1,185 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use cryptowallet::wallet::core::{Bip44ChangeVal, CoinType, NewAddressParams, Wallet};
+    use cryptowallet::wallet::path::Purpose;
+
+    use crate::common::{temp_wallet_path, ZERO_ENTROPY_PHRASE_12};
+
+    fn wallet_with_accounts(name: &str) -> Wallet {
+        let path = temp_wallet_path(name);
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        for purpose in [Purpose::Bip44, Purpose::Bip49, Purpose::Bip84] {
+            wallet
+                .new_account(CoinType::Bitcoin, purpose, "main")
+                .unwrap();
+        }
+        wallet
+    }
+
+    #[test]
+    fn xpubs_use_the_slip132_version_of_their_purpose() {
+        let wallet = wallet_with_accounts("export_xpubs");
+
+        // BIP44, BIP49 and BIP84 test vectors
+        let bip44 = wallet
+            .export_account_xpub(CoinType::Bitcoin, Purpose::Bip44, 0)
+            .unwrap();
+        assert_eq!(bip44.extended_key, "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj");
+        let bip49 = wallet
+            .export_account_xpub(CoinType::Bitcoin, Purpose::Bip49, 0)
+            .unwrap();
+        assert_eq!(bip49.extended_key, "ypub6Ww3ibxVfGzLrAH1PNcjyAWenMTbbAosGNB6VvmSEgytSER9azLDWCxoJwW7Ke7icmizBMXrzBx9979FfaHxHcrArf3zbeJJJUZPf663zsP");
+        let bip84 = wallet
+            .export_account_xpub(CoinType::Bitcoin, Purpose::Bip84, 0)
+            .unwrap();
+        assert_eq!(bip84.extended_key, "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs");
+
+        assert_eq!(bip84.master_fingerprint.as_deref(), Some("73c5da0a"));
+        assert_eq!(bip84.origin_path.to_string(), "m/84'/0'/0'");
+        assert!(bip84
+            .to_string()
+            .starts_with("[73c5da0a/84h/0h/0h]zpub6rFR7y4Q2Aij"));
+    }
+
+    #[test]
+    fn xprvs_use_the_slip132_version_of_their_purpose() {
+        let wallet = wallet_with_accounts("export_xprvs");
+
+        let bip84 = wallet
+            .export_account_xprv(CoinType::Bitcoin, Purpose::Bip84, 0)
+            .unwrap();
+        assert_eq!(bip84.extended_key, "zprvAdG4iTXWBoARxkkzNpNh8r6Qag3irQB8PzEMkAFeTRXxHpbF9z4QgEvBRmfvqWvGp42t42nvgGpNgYSJA9iefm1yYNZKEm7z6qUWCroSQnE");
+        assert!(wallet
+            .export_account_xprv(CoinType::Bitcoin, Purpose::Bip44, 0)
+            .unwrap()
+            .extended_key
+            .starts_with("xprv"));
+        // unknown accounts are not derived on the fly
+        assert!(wallet
+            .export_account_xprv(CoinType::Bitcoin, Purpose::Bip84, 1)
+            .is_err());
+    }
+
+    #[test]
+    fn watch_only_wallets_export_only_their_xpub() {
+        let wallet = wallet_with_accounts("export_source");
+        let zpub = wallet
+            .export_account_xpub(CoinType::Bitcoin, Purpose::Bip84, 0)
+            .unwrap();
+
+        let path = temp_wallet_path("export_watch_only");
+        let watch_only = Wallet::watch_only_at(
+            &path,
+            &zpub.extended_key,
+            CoinType::Bitcoin,
+            Purpose::Bip84,
+            "payments",
+            "correct horse",
+        )
+        .unwrap();
+
+        let exported = watch_only
+            .export_account_xpub(CoinType::Bitcoin, Purpose::Bip84, 0)
+            .unwrap();
+        assert_eq!(exported.extended_key, zpub.extended_key);
+        assert_eq!(exported.master_fingerprint, None);
+        assert_eq!(exported.to_string(), zpub.extended_key);
+        assert!(watch_only
+            .export_account_xprv(CoinType::Bitcoin, Purpose::Bip84, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn litecoin_and_dogecoin_use_their_own_versions() {
+        let path = temp_wallet_path("export_altcoins");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        for (coin, purpose) in [
+            (CoinType::Litecoin, Purpose::Bip44),
+            (CoinType::Litecoin, Purpose::Bip49),
+            (CoinType::Dogecoin, Purpose::Bip44),
+        ] {
+            wallet.new_account(coin, purpose, "main").unwrap();
+        }
+        assert!(wallet
+            .new_account(CoinType::Dogecoin, Purpose::Bip84, "main")
+            .is_err());
+
+        let export = |coin, purpose| {
+            wallet
+                .export_account_xpub(coin, purpose, 0)
+                .unwrap()
+                .extended_key
+        };
+        assert!(export(CoinType::Litecoin, Purpose::Bip44).starts_with("Ltub"));
+        assert!(export(CoinType::Litecoin, Purpose::Bip49).starts_with("Mtub"));
+        assert!(export(CoinType::Dogecoin, Purpose::Bip44).starts_with("dgub"));
+        assert!(wallet
+            .export_account_xprv(CoinType::Dogecoin, Purpose::Bip44, 0)
+            .unwrap()
+            .extended_key
+            .starts_with("dgpv"));
+    }
+
+    #[test]
+    fn watch_only_wallets_accept_ltub_mtub_and_dgub() {
+        let path = temp_wallet_path("export_altcoin_source");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+
+        for (coin, purpose, version) in [
+            (CoinType::Litecoin, Purpose::Bip44, "Ltub"),
+            (CoinType::Litecoin, Purpose::Bip49, "Mtub"),
+            (CoinType::Dogecoin, Purpose::Bip44, "dgub"),
+        ] {
+            wallet.new_account(coin, purpose, "main").unwrap();
+            let prms = NewAddressParams::new(coin, 0).purpose(purpose);
+            wallet.new_address(prms).unwrap();
+            let xpub = wallet.export_account_xpub(coin, purpose, 0).unwrap();
+            assert!(xpub.extended_key.starts_with(version));
+
+            let path = temp_wallet_path(&format!("export_watch_only_{}", version));
+            let mut watch_only = Wallet::watch_only_at(
+                &path,
+                &xpub.extended_key,
+                coin,
+                purpose,
+                "payments",
+                "correct horse",
+            )
+            .unwrap();
+            let addresses = watch_only.new_addresses(prms, 1).unwrap();
+
+            let expected = &wallet.coins[&coin][&purpose].accounts[&0].changes
+                [&Bip44ChangeVal::RECEIVING]
+                .addresses[&0];
+            assert_eq!(addresses[0].address, expected.address);
+            assert_eq!(
+                watch_only
+                    .export_account_xpub(coin, purpose, 0)
+                    .unwrap()
+                    .extended_key,
+                xpub.extended_key
+            );
+        }
+
+        // private keys are refused with the right error in every version
+        let dgpv = wallet
+            .export_account_xprv(CoinType::Dogecoin, Purpose::Bip44, 0)
+            .unwrap();
+        let path = temp_wallet_path("export_watch_only_dgpv");
+        let error = Wallet::watch_only_at(
+            &path,
+            &dgpv.extended_key,
+            CoinType::Dogecoin,
+            Purpose::Bip44,
+            "payments",
+            "correct horse",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("not a private key"));
+    }
+}
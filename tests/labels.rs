@@ -0,0 +1,156 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use cryptowallet::wallet::core::{Bip44ChangeVal, CoinType, NewAddressParams, Wallet};
+    use cryptowallet::wallet::labels::{parse_labels, LabelType};
+    use cryptowallet::wallet::path::Purpose;
+
+    use crate::common::{temp_wallet_path, ZERO_ENTROPY_PHRASE_12};
+
+    const FIRST_ADDRESS: &str = "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu";
+
+    /// BIP84 account 0 with its first two receiving addresses
+    fn wallet_with_addresses(path: &Path) -> Wallet {
+        let mut wallet =
+            Wallet::from_mnemonic_at(path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        wallet
+            .new_account(CoinType::Bitcoin, Purpose::Bip84, "Account 0")
+            .unwrap();
+        let prms = NewAddressParams::new(CoinType::Bitcoin, 0).purpose(Purpose::Bip84);
+        wallet.new_addresses(prms, 2).unwrap();
+        wallet
+    }
+
+    #[test]
+    fn annotations_are_saved_with_the_wallet() {
+        let path = temp_wallet_path("labels_saved");
+        let mut wallet = wallet_with_addresses(&path);
+        wallet
+            .rename_account(CoinType::Bitcoin, Purpose::Bip84, 0, "deposits")
+            .unwrap();
+        wallet
+            .annotate_account(CoinType::Bitcoin, Purpose::Bip84, 0, |annotations| {
+                annotations.note = Some("customer deposits".to_string());
+                annotations.archived = true;
+            })
+            .unwrap();
+        wallet
+            .label_address(FIRST_ADDRESS, Some("customer 42"))
+            .unwrap();
+        wallet
+            .annotate_address(FIRST_ADDRESS, |annotations| {
+                annotations.tags = vec!["kyc".to_string(), "eu".to_string()]
+            })
+            .unwrap();
+        assert!(wallet.label_address("bc1qnotmine", Some("x")).is_err());
+        assert!(wallet
+            .rename_account(CoinType::Bitcoin, Purpose::Bip84, 1, "missing")
+            .is_err());
+        wallet.save_to_file().unwrap();
+
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+        let account = &reloaded.coins[&CoinType::Bitcoin][&Purpose::Bip84].accounts[&0];
+        assert_eq!(account.name, "deposits");
+        assert_eq!(
+            account.annotations.note.as_deref(),
+            Some("customer deposits")
+        );
+        assert!(account.annotations.archived);
+        let receiving = &account.changes[&Bip44ChangeVal::RECEIVING];
+        let address = &receiving.addresses[&0];
+        assert_eq!(address.label.as_deref(), Some("customer 42"));
+        assert_eq!(address.annotations.tags, ["kyc", "eu"]);
+        assert!(!address.annotations.archived);
+        assert_eq!(receiving.addresses[&1].label, None);
+        // archived accounts are still found
+        assert!(reloaded.find_address(FIRST_ADDRESS).is_some());
+    }
+
+    #[test]
+    fn labels_round_trip_through_bip329() {
+        let path = temp_wallet_path("labels_export");
+        let mut wallet = wallet_with_addresses(&path);
+        wallet
+            .rename_account(CoinType::Bitcoin, Purpose::Bip84, 0, "deposits")
+            .unwrap();
+        wallet
+            .label_address(FIRST_ADDRESS, Some("customer 42"))
+            .unwrap();
+
+        let exported = wallet.export_labels().unwrap();
+        let records = parse_labels(&exported).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].label_type, LabelType::Xpub);
+        assert_eq!(records[0].reference, "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs");
+        assert_eq!(
+            records[0].origin.as_deref(),
+            Some("wpkh([73c5da0a/84h/0h/0h])")
+        );
+        assert_eq!(records[1].label_type, LabelType::Addr);
+        assert_eq!(records[1].reference, FIRST_ADDRESS);
+        assert_eq!(records[1].label.as_deref(), Some("customer 42"));
+
+        let path = temp_wallet_path("labels_import");
+        let mut restored = wallet_with_addresses(&path);
+        assert_eq!(restored.import_labels(&exported).unwrap(), 2);
+        let account = &restored.coins[&CoinType::Bitcoin][&Purpose::Bip84].accounts[&0];
+        assert_eq!(account.name, "deposits");
+        assert_eq!(
+            account.changes[&Bip44ChangeVal::RECEIVING].addresses[&0]
+                .label
+                .as_deref(),
+            Some("customer 42")
+        );
+
+        // other wallets export the same account as a plain xpub, the rest is not for this wallet
+        let imported = [
+            r#"{"type":"xpub","ref":"xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V","label":"savings"}"#,
+            r#"{"type":"tx","ref":"f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd","label":"rent"}"#,
+            r#"{"type":"addr","ref":"bc1qnotmine","label":"ignored"}"#,
+        ]
+        .join("\n");
+        assert_eq!(restored.import_labels(&imported).unwrap(), 1);
+        assert_eq!(
+            restored.coins[&CoinType::Bitcoin][&Purpose::Bip84].accounts[&0].name,
+            "savings"
+        );
+
+        let error = restored.import_labels("\n{\"type\":\"addr\"}").unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn altcoin_xpub_labels_round_trip() {
+        let path = temp_wallet_path("labels_altcoins");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        let accounts = [
+            (CoinType::Litecoin, Purpose::Bip44, "Ltub"),
+            (CoinType::Litecoin, Purpose::Bip49, "Mtub"),
+            (CoinType::Dogecoin, Purpose::Bip44, "dgub"),
+        ];
+        for (coin, purpose, version) in accounts {
+            wallet.new_account(coin, purpose, version).unwrap();
+        }
+
+        let exported = wallet.export_labels().unwrap();
+        let records = parse_labels(&exported).unwrap();
+        assert_eq!(records.len(), 3);
+        for (coin, purpose, version) in accounts {
+            wallet.rename_account(coin, purpose, 0, "renamed").unwrap();
+            let record = records
+                .iter()
+                .find(|record| record.label.as_deref() == Some(version))
+                .unwrap();
+            assert!(record.reference.starts_with(version));
+        }
+
+        assert_eq!(wallet.import_labels(&exported).unwrap(), 3);
+        for (coin, purpose, version) in accounts {
+            assert_eq!(wallet.coins[&coin][&purpose].accounts[&0].name, version);
+        }
+    }
+}
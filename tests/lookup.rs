@@ -0,0 +1,204 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use cryptowallet::wallet::core::{Bip44ChangeVal, CoinType, NewAddressParams, Wallet};
+    use cryptowallet::wallet::path::Purpose;
+
+    use crate::common::{temp_wallet_path, ZERO_ENTROPY_PHRASE_12};
+
+    #[test]
+    fn finds_addresses_of_every_coin() {
+        let path = temp_wallet_path("lookup");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        wallet
+            .new_account(CoinType::Ethereum, Purpose::Bip44, "main")
+            .unwrap();
+        wallet
+            .new_account(CoinType::Bitcoin, Purpose::Bip84, "main")
+            .unwrap();
+        let ethereum = NewAddressParams::new(CoinType::Ethereum, 0);
+        let bitcoin = NewAddressParams::new(CoinType::Bitcoin, 0)
+            .purpose(Purpose::Bip84)
+            .change(Bip44ChangeVal::INTERNAL);
+        wallet.new_addresses(ethereum, 2).unwrap();
+        wallet.new_addresses(bitcoin, 1).unwrap();
+
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        for wallet in [wallet, reloaded] {
+            let location = wallet
+                .find_address("0x9858EfFD232B4033E47d90003D41EC34EcaEda94")
+                .unwrap();
+            assert_eq!(location.coin, CoinType::Ethereum);
+            assert_eq!(location.purpose, Purpose::Bip44);
+            assert_eq!(location.account, 0);
+            assert_eq!(location.change, Bip44ChangeVal::RECEIVING);
+            assert_eq!(location.index, 0);
+            assert_eq!(location.path.to_string(), "m/44'/60'/0'/0/0");
+            assert!(!location.legacy);
+            for form in [
+                "0x9858effd232b4033e47d90003d41ec34ecaeda94",
+                "0X9858EFFD232B4033E47D90003D41EC34ECAEDA94",
+            ] {
+                assert_eq!(wallet.find_address(form), Some(location));
+            }
+
+            let location = wallet
+                .find_address("bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el")
+                .unwrap();
+            assert_eq!(location.change, Bip44ChangeVal::INTERNAL);
+            assert_eq!(location.path.to_string(), "m/84'/0'/0'/1/0");
+
+            assert!(wallet
+                .find_address("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu")
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn evm_addresses_are_stored_in_full() {
+        let path = temp_wallet_path("lookup_evm_full");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        wallet
+            .new_account(CoinType::Ethereum, Purpose::Bip44, "main")
+            .unwrap();
+        let address = &wallet
+            .new_addresses(NewAddressParams::new(CoinType::Ethereum, 0), 1)
+            .unwrap()[0];
+        assert_eq!(
+            address.address,
+            "0x9858effd232b4033e47d90003d41ec34ecaeda94"
+        );
+    }
+
+    #[test]
+    fn abbreviated_evm_addresses_are_repaired_on_load() {
+        let path = temp_wallet_path("lookup_evm_repair");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        wallet
+            .new_account(CoinType::Ethereum, Purpose::Bip44, "main")
+            .unwrap();
+        wallet
+            .new_address(NewAddressParams::new(CoinType::Ethereum, 0))
+            .unwrap();
+        let account = wallet
+            .coins
+            .get_mut(&CoinType::Ethereum)
+            .unwrap()
+            .get_mut(&Purpose::Bip44)
+            .unwrap()
+            .accounts
+            .get_mut(&0)
+            .unwrap();
+        let address = account
+            .changes
+            .get_mut(&Bip44ChangeVal::RECEIVING)
+            .unwrap()
+            .addresses
+            .get_mut(&0)
+            .unwrap();
+        // what earlier versions stored
+        address.address = "0x9858…da94".to_string();
+        wallet.save_to_file().unwrap();
+
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+        let address = &reloaded.coins[&CoinType::Ethereum][&Purpose::Bip44].accounts[&0].changes
+            [&Bip44ChangeVal::RECEIVING]
+            .addresses[&0];
+        assert_eq!(
+            address.address,
+            "0x9858effd232b4033e47d90003d41ec34ecaeda94"
+        );
+        assert!(reloaded
+            .find_address("0x9858effd232b4033e47d90003d41ec34ecaeda94")
+            .is_some());
+    }
+
+    #[test]
+    fn baseline_evm_addresses_are_found_in_full() {
+        let path = temp_wallet_path("lookup_evm_baseline");
+        fs::write(&path, include_str!("fixtures/baseline_wallet.json")).unwrap();
+        let wallet = Wallet::from_path(&path, "correct horse", None).unwrap();
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        for wallet in [wallet, reloaded] {
+            let addresses = wallet.coins[&CoinType::Ethereum][&Purpose::Bip44].accounts[&0].changes
+                [&Bip44ChangeVal::RECEIVING]
+                .addresses
+                .values();
+            for address in addresses {
+                // the baseline stored "0x815d…56cc"
+                assert_eq!(address.address.len(), 42);
+                assert_eq!(address.address, address.address_checksummed.to_lowercase());
+                let location = wallet.find_address(&address.address).unwrap();
+                assert!(!location.legacy);
+                let legacy = address.legacy.as_ref().unwrap();
+                let location = wallet.find_address(&legacy.address.to_lowercase()).unwrap();
+                assert!(location.legacy);
+            }
+        }
+    }
+
+    #[test]
+    fn addresses_shared_by_test_networks_keep_every_account() {
+        let path = temp_wallet_path("lookup_test_networks");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        for coin in [CoinType::BitcoinSignet, CoinType::BitcoinTestnet] {
+            wallet.new_account(coin, Purpose::Bip84, "main").unwrap();
+        }
+        let testnet = wallet
+            .new_addresses(
+                NewAddressParams::new(CoinType::BitcoinTestnet, 0).purpose(Purpose::Bip84),
+                1,
+            )
+            .unwrap()[0]
+            .address
+            .clone();
+        let signet = wallet
+            .new_addresses(
+                NewAddressParams::new(CoinType::BitcoinSignet, 0).purpose(Purpose::Bip84),
+                1,
+            )
+            .unwrap()[0]
+            .address
+            .clone();
+        assert_eq!(testnet, signet);
+        assert!(testnet.starts_with("tb1q"));
+        wallet.label_address(&testnet, Some("faucet")).unwrap();
+        wallet.save_to_file().unwrap();
+
+        let reloaded = Wallet::from_path(&path, "correct horse", None).unwrap();
+
+        for wallet in [wallet, reloaded] {
+            let coins: Vec<CoinType> = wallet
+                .find_address_locations(&testnet)
+                .iter()
+                .map(|location| location.coin)
+                .collect();
+            assert_eq!(coins, [CoinType::BitcoinTestnet, CoinType::BitcoinSignet]);
+            assert_eq!(
+                wallet.find_address(&testnet).unwrap().coin,
+                CoinType::BitcoinTestnet
+            );
+            for location in wallet.find_address_locations(&testnet) {
+                assert_eq!(location.path.to_string(), "m/84'/1'/0'/0/0");
+            }
+            assert!(wallet.find_address_locations("tb1qunknown").is_empty());
+
+            let labels = wallet.export_labels().unwrap();
+            let records: Vec<&str> = labels
+                .lines()
+                .filter(|line| line.contains(&testnet))
+                .collect();
+            assert_eq!(records.len(), 1);
+            assert!(records[0].contains("faucet"));
+        }
+    }
+}
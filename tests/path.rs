@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use cryptowallet::wallet::path::{ChildIndex, DerivationPath, Purpose};
+
+    #[test]
+    fn parses_both_hardened_markers() {
+        let apostrophe: DerivationPath = "m/84'/0'/0'/0/5".parse().unwrap();
+        let h: DerivationPath = "m/84h/0h/0h/0/5".parse().unwrap();
+        assert_eq!(apostrophe, h);
+        assert_eq!(apostrophe.depth(), 5);
+        assert_eq!(
+            apostrophe.children()[0],
+            ChildIndex {
+                index: 84,
+                hardened: true
+            }
+        );
+    }
+
+    #[test]
+    fn prints_apostrophe_or_h() {
+        let path: DerivationPath = "m/86h/1h/2h/1/9".parse().unwrap();
+        assert_eq!(path.to_string(), "m/86'/1'/2'/1/9");
+        assert_eq!(format!("{:#}", path), "m/86h/1h/2h/1/9");
+        assert_eq!(DerivationPath::master().to_string(), "m");
+    }
+
+    #[test]
+    fn parses_arbitrary_depth() {
+        let path: DerivationPath = "m/83696968'/39'/0'/24'/0'".parse().unwrap();
+        assert_eq!(path.depth(), 5);
+        assert_eq!(path.purpose(), None);
+        assert!(path.validate_purpose().is_ok());
+
+        let deep: DerivationPath = "m/1/2/3/4/5/6/7/8/9/10".parse().unwrap();
+        assert_eq!(deep.depth(), 10);
+    }
+
+    #[test]
+    fn recognises_purpose_levels() {
+        for (path, purpose) in [
+            ("m/44'/0'/0'", Purpose::Bip44),
+            ("m/49'/0'/0'", Purpose::Bip49),
+            ("m/84'/0'/0'", Purpose::Bip84),
+            ("m/86'/0'/0'", Purpose::Bip86),
+        ] {
+            let path: DerivationPath = path.parse().unwrap();
+            assert_eq!(path.purpose(), Some(purpose));
+        }
+        // an unhardened 44 is not a purpose level
+        let path: DerivationPath = "m/44/0'/0'".parse().unwrap();
+        assert_eq!(path.purpose(), None);
+    }
+
+    #[test]
+    fn builds_purpose_paths() {
+        let path = DerivationPath::for_address(Purpose::Bip49, 1, 2, 1, 7).unwrap();
+        assert_eq!(path.to_string(), "m/49'/1'/2'/1/7");
+        let account = DerivationPath::for_account(Purpose::Bip84, 0, 3).unwrap();
+        assert_eq!(account.to_string(), "m/84'/0'/3'");
+        assert_eq!(
+            account.child(ChildIndex::normal(0).unwrap()).to_string(),
+            "m/84'/0'/3'/0"
+        );
+    }
+
+    #[test]
+    fn validates_purpose_hardening() {
+        let check = |path: &str| path.parse::<DerivationPath>().unwrap().validate_purpose();
+        assert!(check("m/44'/60'/0'/0/0").is_ok());
+        assert!(check("m/44'/60'").is_ok());
+        assert!(check("m/44'/60/0'/0/0").is_err());
+        assert!(check("m/84'/0'/0'/0'/0").is_err());
+        assert!(check("m/84'/0'/0'/2/0").is_err());
+        assert!(check("m/86'/0'/0'/0/0/0").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_paths_with_precise_errors() {
+        let err = |path: &str| path.parse::<DerivationPath>().unwrap_err().to_string();
+        assert!(err("44'/0'").contains("must start with"));
+        assert!(err("m/44'//0").contains("level 2"));
+        assert!(err("m/44'/x/0").contains("\"x\" at level 2"));
+        assert!(err("m/44'/0''").contains("level 2"));
+        assert!(err("m/2147483648").contains("out of range"));
+        // the largest index is still fine, hardened or not
+        assert!("m/2147483647h".parse::<DerivationPath>().is_ok());
+    }
+
+    #[test]
+    fn serializes_as_string() {
+        let path: DerivationPath = "m/44h/60h/0h/0/1".parse().unwrap();
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(json, "\"m/44'/60'/0'/0/1\"");
+        let back: DerivationPath = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, path);
+    }
+}
@@ -0,0 +1,142 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use cryptowallet::wallet::coins::{adapter, coins_for_address};
+    use cryptowallet::wallet::core::{Bip44ChangeVal, CoinType, NewAddressParams, Wallet};
+    use cryptowallet::wallet::path::Purpose;
+    use cryptowallet::wallet::slip10::Ed25519Key;
+
+    use crate::common::{temp_wallet_path, ZERO_ENTROPY_PHRASE_12};
+
+    // what Phantom and Solflare show for the first account of ZERO_ENTROPY_PHRASE_12
+    const FIRST_ADDRESS: &str = "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk";
+
+    #[test]
+    fn slip10_ed25519_test_vector_1() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = Ed25519Key::from_seed(&seed).unwrap();
+        assert_eq!(
+            hex::encode(master.chain_code()),
+            "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb"
+        );
+        assert_eq!(
+            hex::encode(master.secret_key()),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            hex::encode(master.public_key()),
+            "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed"
+        );
+
+        let child = Ed25519Key::derive_from_path(&seed, &"m/0'/1'".parse().unwrap()).unwrap();
+        assert_eq!(
+            hex::encode(child.chain_code()),
+            "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14"
+        );
+        assert_eq!(
+            hex::encode(child.secret_key()),
+            "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2"
+        );
+        assert_eq!(
+            hex::encode(child.public_key()),
+            "1932a5270f335bed617d5b935c80aedb1a35bd9fc1e31acafd5372c30f5c1187"
+        );
+
+        assert!(Ed25519Key::derive_from_path(&seed, &"m/0'/1".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn solana_accounts_match_phantom() {
+        let path = temp_wallet_path("solana");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        for name in ["Main", "Trading"] {
+            wallet
+                .new_account(CoinType::Solana, Purpose::Bip44, name)
+                .unwrap();
+        }
+        assert!(wallet
+            .new_account(CoinType::Solana, Purpose::Bip84, "segwit")
+            .is_err());
+
+        let first = NewAddressParams::new(CoinType::Solana, 0);
+        wallet.new_address(first).unwrap();
+        // one address per account, like Phantom
+        assert_eq!(
+            wallet.new_address(first).unwrap_err().to_string(),
+            "solana accounts have a single address, create a new account instead"
+        );
+        let second = wallet
+            .new_addresses(NewAddressParams::new(CoinType::Solana, 1), 1)
+            .unwrap();
+
+        let accounts = &wallet.coins[&CoinType::Solana][&Purpose::Bip44].accounts;
+        let address = &accounts[&0].changes[&Bip44ChangeVal::RECEIVING].addresses[&0];
+        assert_eq!(address.path.to_string(), "m/44'/501'/0'/0'");
+        assert_eq!(address.address, FIRST_ADDRESS);
+        assert_eq!(address.address_checksummed, FIRST_ADDRESS);
+        assert_eq!(address.pub_key.len(), 32);
+        assert_eq!(second[0].path.to_string(), "m/44'/501'/1'/0'");
+        assert_ne!(second[0].address, FIRST_ADDRESS);
+
+        assert_eq!(
+            wallet.find_address(FIRST_ADDRESS).unwrap().coin,
+            CoinType::Solana
+        );
+        assert!(wallet
+            .export_account_xpub(CoinType::Solana, Purpose::Bip44, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn solana_addresses_are_32_byte_keys() {
+        let solana = adapter(CoinType::Solana);
+        assert_eq!(solana.slip44(), 501);
+        assert!(solana.validate_address(FIRST_ADDRESS));
+        assert!(solana.validate_address("11111111111111111111111111111111"));
+        assert!(!solana.validate_address("HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3D"));
+        assert!(!solana.validate_address("HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpq0"));
+        assert!(!solana.validate_address("DBus3bamQjgJULBJtYXpEzDWQRwF5iwxgC"));
+        assert!(!solana.validate_address("0x9858EfFD232B4033E47d90003D41EC34EcaEda94"));
+        assert_eq!(coins_for_address(FIRST_ADDRESS), [CoinType::Solana]);
+    }
+
+    #[test]
+    fn labels_import_next_to_solana_accounts() {
+        let path = temp_wallet_path("solana_labels");
+        let mut wallet =
+            Wallet::from_mnemonic_at(&path, ZERO_ENTROPY_PHRASE_12, "correct horse", None).unwrap();
+        wallet
+            .new_account(CoinType::Bitcoin, Purpose::Bip84, "Account 0")
+            .unwrap();
+        wallet
+            .new_account(CoinType::Solana, Purpose::Bip44, "Main")
+            .unwrap();
+        wallet
+            .new_address(NewAddressParams::new(CoinType::Solana, 0))
+            .unwrap();
+        wallet
+            .label_address(FIRST_ADDRESS, Some("staking"))
+            .unwrap();
+
+        let exported = wallet.export_labels().unwrap();
+        wallet
+            .rename_account(CoinType::Bitcoin, Purpose::Bip84, 0, "renamed")
+            .unwrap();
+        wallet.label_address(FIRST_ADDRESS, None).unwrap();
+        // the bitcoin xpub and the solana address
+        assert_eq!(wallet.import_labels(&exported).unwrap(), 2);
+        assert_eq!(
+            wallet.coins[&CoinType::Bitcoin][&Purpose::Bip84].accounts[&0].name,
+            "Account 0"
+        );
+        let solana = &wallet.coins[&CoinType::Solana][&Purpose::Bip44].accounts[&0];
+        assert_eq!(
+            solana.changes[&Bip44ChangeVal::RECEIVING].addresses[&0]
+                .label
+                .as_deref(),
+            Some("staking")
+        );
+    }
+}
@@ -0,0 +1,43 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use cryptowallet::wallet::coins::{adapter, coins_for_address};
+    use cryptowallet::wallet::core::{CoinType, PubKeyForm};
+    use cryptowallet::wallet::path::Purpose;
+
+    use crate::common::pub_key;
+
+    // TronLink for ZERO_ENTROPY_PHRASE_12 at m/44'/195'/0'/0/0
+    const FIRST_ADDRESS: &str = "TUEZSdKsoDHQMeZwihtdoBiN46zxhGWYdH";
+
+    #[test]
+    fn tron_addresses_match_tronlink() {
+        let encoded = adapter(CoinType::Tron)
+            .encode_address(
+                &pub_key("m/44'/195'/0'/0/0"),
+                Some(Purpose::Bip44),
+                PubKeyForm::Compressed,
+            )
+            .unwrap();
+        assert_eq!(encoded.address, FIRST_ADDRESS);
+        assert_eq!(encoded.checksummed, FIRST_ADDRESS);
+        assert_eq!(encoded.key_form, PubKeyForm::Uncompressed);
+        assert_eq!(CoinType::Tron.slip44(), 195);
+    }
+
+    #[test]
+    fn tron_addresses_are_checked() {
+        let tron = adapter(CoinType::Tron);
+        assert_eq!(
+            tron.checksum_address(&format!(" {}\n", FIRST_ADDRESS))
+                .unwrap(),
+            FIRST_ADDRESS
+        );
+        assert!(!tron.validate_address("TUEZSdKsoDHQMeZwihtdoBiN46zxhGWYdJ"));
+        // right checksum, bitcoin version byte
+        assert!(!tron.validate_address("1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA"));
+        assert!(!tron.validate_address("0x9858EfFD232B4033E47d90003D41EC34EcaEda94"));
+        assert_eq!(coins_for_address(FIRST_ADDRESS), [CoinType::Tron]);
+    }
+}